@@ -0,0 +1,464 @@
+//! `flowrs-package`: inspects flow-packages and generates code from them.
+//!
+//! ```text
+//! flowrs-package validate packages/
+//! flowrs-package --packages packages/ show nodes::Scale
+//! flowrs-package --packages packages/ emit nodes::Scale New --param T=f32
+//! flowrs-package --packages packages/ generate flow.json --function build
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::Error;
+use flowrs_package::flow_package::flow::{Flow, FlowCodeOptions, FlowTarget};
+use flowrs_package::flow_package::package::{
+    Argument, ArgumentConstruction, ArgumentPassing, Constructor, Namespace, Type,
+};
+use flowrs_package::flow_package::package_manager::{LoadMode, PackageManager};
+use flowrs_package::flow_package::validate::Severity;
+
+const USAGE: &str = "\
+Usage: flowrs-package [--packages <dir>] <command>
+
+Commands:
+  validate <dir>                  Check all packages of a folder, fails on errors
+  list                            List the types of all packages
+  show <type>                     Show ports, type parameters and constructors of a type
+  emit <type> <constructor>       Print the code constructing an object of a type
+      [--param <T>=<type>]...     Bind a type parameter
+      [--name <name>]             Name of the object, `object` by default
+  generate <flow.json>            Print the code of a flow, `fn main()` by default
+      [--function <name>]         Emit `pub fn <name>(data, change_observer)` instead
+      [--error-type <type>]       Propagate construction errors as this type
+      [--connect <function>]      Function connecting ports
+      [--formatted]               Format the code with prettyplease
+
+Options:
+  --packages <dir>                Folder the packages are loaded from, `packages` by default";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Validate {
+        directory: PathBuf,
+    },
+    List,
+    Show {
+        type_name: String,
+    },
+    Emit {
+        type_name: String,
+        constructor: String,
+        type_parameters: HashMap<String, String>,
+        name: String,
+    },
+    Generate {
+        flow: PathBuf,
+        function: Option<String>,
+        error_type: Option<String>,
+        connect: Option<String>,
+        formatted: bool,
+    },
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+struct Arguments {
+    packages: PathBuf,
+    command: Command,
+}
+
+fn main() -> ExitCode {
+    let arguments = match parse(env::args().skip(1).collect()) {
+        Ok(arguments) => arguments,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&arguments) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse(args: Vec<String>) -> Result<Arguments, Error> {
+    let mut packages = PathBuf::from("packages");
+    let mut positional = Vec::new();
+    let mut options: Vec<(String, Option<String>)> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                return Ok(Arguments {
+                    packages,
+                    command: Command::Help,
+                })
+            }
+            "--formatted" => options.push((arg, None)),
+            "--packages" | "--param" | "--name" | "--function" | "--error-type" | "--connect" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| Error::msg(format!("Missing value of '{}'.", arg)))?;
+                if arg == "--packages" {
+                    packages = PathBuf::from(value);
+                } else {
+                    options.push((arg, Some(value)));
+                }
+            }
+            option if option.starts_with("--") => {
+                return Err(Error::msg(format!("Unknown option '{}'.", option)))
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let Some((command, operands)) = positional.split_first() else {
+        return Err(Error::msg("Missing command."));
+    };
+    let allowed: &[&str] = match command.as_str() {
+        "emit" => &["--param", "--name"],
+        "generate" => &["--function", "--error-type", "--connect", "--formatted"],
+        _ => &[],
+    };
+    if let Some((option, _)) = options.iter().find(|(o, _)| !allowed.contains(&o.as_str())) {
+        return Err(Error::msg(format!(
+            "Option '{}' does not apply to '{}'.",
+            option, command
+        )));
+    }
+    let option = |name: &str| -> Option<String> {
+        options
+            .iter()
+            .rev()
+            .find(|(o, _)| o == name)
+            .and_then(|(_, v)| v.clone())
+    };
+
+    let command = match (command.as_str(), operands) {
+        ("validate", [directory]) => Command::Validate {
+            directory: PathBuf::from(directory),
+        },
+        ("list", []) => Command::List,
+        ("show", [type_name]) => Command::Show {
+            type_name: type_name.clone(),
+        },
+        ("emit", [type_name, constructor]) => {
+            let mut type_parameters = HashMap::new();
+            for (_, binding) in options.iter().filter(|(o, _)| o == "--param") {
+                let binding = binding.as_deref().unwrap_or_default();
+                let (parameter, bound) = binding.split_once('=').ok_or_else(|| {
+                    Error::msg(format!("'{}' is no binding like 'T=i32'.", binding))
+                })?;
+                type_parameters.insert(parameter.trim().to_string(), bound.trim().to_string());
+            }
+            Command::Emit {
+                type_name: type_name.clone(),
+                constructor: constructor.clone(),
+                type_parameters,
+                name: option("--name").unwrap_or_else(|| "object".to_string()),
+            }
+        }
+        ("generate", [flow]) => Command::Generate {
+            flow: PathBuf::from(flow),
+            function: option("--function"),
+            error_type: option("--error-type"),
+            connect: option("--connect"),
+            formatted: options.iter().any(|(o, _)| o == "--formatted"),
+        },
+        ("help", []) => Command::Help,
+        ("validate" | "list" | "show" | "emit" | "generate" | "help", _) => {
+            return Err(Error::msg(format!(
+                "Wrong number of arguments for '{}'.",
+                command
+            )))
+        }
+        _ => return Err(Error::msg(format!("Unknown command '{}'.", command))),
+    };
+
+    Ok(Arguments { packages, command })
+}
+
+/// Runs a command. `Ok(false)` means the command worked, but found problems.
+fn run(arguments: &Arguments) -> Result<bool, Error> {
+    match &arguments.command {
+        Command::Validate { directory } => validate(directory),
+        Command::List => {
+            list(&load_packages(&arguments.packages)?);
+            Ok(true)
+        }
+        Command::Show { type_name } => {
+            show(&load_packages(&arguments.packages)?, type_name)?;
+            Ok(true)
+        }
+        Command::Emit {
+            type_name,
+            constructor,
+            type_parameters,
+            name,
+        } => {
+            let pm = load_packages(&arguments.packages)?;
+            let type_desc = pm.resolve_type(type_name)?.type_desc;
+            let constructor = type_desc.constructors.get(constructor).ok_or_else(|| {
+                Error::msg(format!(
+                    "Type '{}' has no constructor '{}'.",
+                    type_name, constructor
+                ))
+            })?;
+            let obj_desc = type_desc.object_description(type_name, name, type_parameters)?;
+            println!(
+                "{}",
+                constructor
+                    .emit_code_template(
+                        &obj_desc,
+                        type_parameters,
+                        &pm,
+                        &Namespace::with_context_objects(&pm)
+                    )?
+                    .trim()
+            );
+            Ok(true)
+        }
+        Command::Generate {
+            flow,
+            function,
+            error_type,
+            connect,
+            formatted,
+        } => {
+            let pm = load_packages(&arguments.packages)?;
+            let json = fs::read_to_string(flow)
+                .map_err(|e| Error::msg(format!("Could not read '{}': {}", flow.display(), e)))?;
+            let flow: Flow = serde_json::from_str(&json)?;
+
+            // The observer `NewWithObserver` constructors get, see `Namespace::with_context_objects`.
+            let mut options = FlowCodeOptions {
+                target: match function {
+                    Some(name) => FlowTarget::Function {
+                        name: name.clone(),
+                        parameters: vec![
+                            "change_observer: &flowrs::node::ChangeObserver".to_string()
+                        ],
+                    },
+                    None => FlowTarget::Main {
+                        prelude: vec!["let change_observer = flowrs::node::ChangeObserver::new();"
+                            .to_string()],
+                    },
+                },
+                error_type: error_type.clone(),
+                ..Default::default()
+            };
+            if let Some(connect) = connect {
+                options.connect_function = connect.clone();
+            }
+            if *formatted {
+                print!("{}", flow.emit_formatted_code(&pm, &options)?);
+            } else {
+                print!("{}", flow.emit_code(&pm, &options)?);
+            }
+            Ok(true)
+        }
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+    }
+}
+
+/// Loads the packages of a folder, failing on the first broken one.
+fn load_packages(directory: &Path) -> Result<PackageManager, Error> {
+    let (pm, _) = PackageManager::load_from_folder(&directory.to_string_lossy(), LoadMode::Strict)?;
+    Ok(pm)
+}
+
+fn validate(directory: &Path) -> Result<bool, Error> {
+    let (pm, report) =
+        PackageManager::load_from_folder(&directory.to_string_lossy(), LoadMode::Lenient)?;
+    let mut errors = 0;
+    let mut warnings = 0;
+    for error in &report.errors {
+        println!("error: {}", error);
+        errors += 1;
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        // Files that cannot be read were reported while loading.
+        let Ok(package) = PackageManager::load_package_file(&path) else {
+            continue;
+        };
+        for diagnostic in package.validate(&pm) {
+            match diagnostic.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            println!("{}: {}", path.display(), diagnostic);
+        }
+    }
+
+    println!("{} error(s), {} warning(s)", errors, warnings);
+    Ok(errors == 0)
+}
+
+fn list(pm: &PackageManager) {
+    let mut packages = pm.get_all_packages();
+    packages.retain(|p| p.name != "built-in");
+    packages.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then(a.semver().ok().cmp(&b.semver().ok()))
+    });
+    for package in packages {
+        println!("{}@{}", package.name, package.version);
+        for (path, _) in package.types() {
+            println!("  {}", path);
+        }
+    }
+}
+
+fn show(pm: &PackageManager, type_name: &str) -> Result<(), Error> {
+    let resolved = pm.resolve_type(type_name)?;
+    let type_desc: &Type = resolved.type_desc;
+    println!(
+        "{} ({}@{})",
+        type_name, resolved.package.name, resolved.package.version
+    );
+
+    if let Some(type_parameters) = &type_desc.type_parameters {
+        println!("type parameters:");
+        for tp in type_parameters {
+            if tp.constraints.is_empty() {
+                println!("  {}", tp.name);
+            } else {
+                println!("  {}: {}", tp.name, tp.constraints.join(" + "));
+            }
+        }
+    }
+    for (title, ports) in [
+        (
+            "inputs",
+            type_desc.inputs.as_ref().map(|inputs| {
+                inputs
+                    .iter()
+                    .map(|(name, input)| (name, input.input_type.to_string()))
+                    .collect::<Vec<_>>()
+            }),
+        ),
+        (
+            "outputs",
+            type_desc.outputs.as_ref().map(|outputs| {
+                outputs
+                    .iter()
+                    .map(|(name, output)| (name, output.output_type.to_string()))
+                    .collect::<Vec<_>>()
+            }),
+        ),
+    ] {
+        let Some(mut ports) = ports else {
+            continue;
+        };
+        ports.sort();
+        println!("{}:", title);
+        for (name, port_type) in ports {
+            println!("  {}: {}", name, port_type);
+        }
+    }
+
+    let mut constructors: Vec<_> = type_desc.constructors.iter().collect();
+    constructors.sort_by_key(|(name, _)| *name);
+    println!("constructors:");
+    for (name, constructor) in constructors {
+        println!("  {}: {}", name, describe_constructor(constructor));
+    }
+    if !type_desc.traits.is_empty() {
+        println!("traits: {}", type_desc.traits.join(", "));
+    }
+    Ok(())
+}
+
+fn describe_constructor(constructor: &Constructor) -> String {
+    let call = |function_name: &Option<String>, arguments: Vec<String>, fallible: bool| {
+        format!(
+            "{}({}){}",
+            function_name.as_deref().unwrap_or("new"),
+            arguments.join(", "),
+            if fallible { " -> Result" } else { "" }
+        )
+    };
+    match constructor {
+        Constructor::New {
+            function_name,
+            fallible,
+        } => call(function_name, Vec::new(), *fallible),
+        Constructor::NewWithObserver {
+            function_name,
+            fallible,
+        } => call(
+            function_name,
+            vec!["change_observer".to_string()],
+            *fallible,
+        ),
+        Constructor::NewWithObserverAndContext {
+            function_name,
+            fallible,
+        } => call(
+            function_name,
+            vec!["change_observer".to_string(), "context".to_string()],
+            *fallible,
+        ),
+        Constructor::NewWithArbitraryArgs {
+            function_name,
+            arguments,
+            fallible,
+        } => call(
+            function_name,
+            arguments.iter().map(describe_argument).collect(),
+            *fallible,
+        ),
+        Constructor::FromJson => "from Json".to_string(),
+        Constructor::FromDefault => "Default::default()".to_string(),
+        Constructor::FromCode { arguments, .. } if arguments.is_empty() => {
+            "code template".to_string()
+        }
+        Constructor::FromCode { arguments, .. } => format!(
+            "code template with {}",
+            arguments
+                .iter()
+                .map(describe_argument)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn describe_argument(arg: &Argument) -> String {
+    let passing = match arg.passing {
+        ArgumentPassing::Move => "",
+        ArgumentPassing::Clone => "clone of ",
+        ArgumentPassing::Reference => "&",
+        ArgumentPassing::MutableReference => "&mut ",
+    };
+    let construction = match &arg.construction {
+        ArgumentConstruction::Constructor(constructor) => format!("constructor {}", constructor),
+        ArgumentConstruction::ExistingObject() => "context object".to_string(),
+        ArgumentConstruction::Shared(name) => format!("shared object '{}'", name),
+    };
+    format!(
+        "{}{}: {} [{}]",
+        passing, arg.name, arg.arg_type, construction
+    )
+}
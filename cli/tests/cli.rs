@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_flowrs-package"))
+        .args(args)
+        .output()
+        .expect("could not run flowrs-package.")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn validate_test() {
+    let output = run(&["validate", &fixture("packages")]);
+    assert!(output.status.success());
+    assert_eq!("0 error(s), 0 warning(s)\n", stdout(&output));
+
+    let output = run(&["validate", &fixture("broken")]);
+    assert_eq!(Some(1), output.status.code());
+    let stdout = stdout(&output);
+    assert!(stdout.contains(
+        "nodes.json: error: /crates/nodes/types/Print/constructors/New/NewWithArbitraryArgs\
+         /arguments/0/construction/Constructor: Type 'u32' has no constructor 'Missing'."
+    ));
+    assert!(stdout.contains("nodes.json: error: /version: 'one' is no semantic version"));
+}
+
+#[test]
+fn inspect_test() {
+    let packages = fixture("packages");
+
+    let output = run(&["--packages", &packages, "list"]);
+    assert!(output.status.success());
+    assert_eq!(
+        "nodes@1.0.0\n  nodes::Print\n  nodes::Scale\n",
+        stdout(&output)
+    );
+
+    let output = run(&["--packages", &packages, "show", "nodes::Scale"]);
+    assert!(output.status.success());
+    assert_eq!(
+        "nodes::Scale (nodes@1.0.0)\n\
+         type parameters:\n  T: Copy\n\
+         inputs:\n  input: T\n\
+         outputs:\n  output: T\n\
+         constructors:\n  New: new(change_observer)\n",
+        stdout(&output)
+    );
+
+    let output = run(&["--packages", &packages, "show", "nodes::Missing"]);
+    assert_eq!(Some(1), output.status.code());
+}
+
+#[test]
+fn emit_test() {
+    let packages = fixture("packages");
+
+    let output = run(&[
+        "--packages",
+        &packages,
+        "emit",
+        "nodes::Scale",
+        "New",
+        "--param",
+        "T=f32",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        "let object = nodes::Scale::<f32>::new(change_observer.clone());\n",
+        stdout(&output)
+    );
+
+    let output = run(&["--packages", &packages, "generate", &fixture("flow.json")]);
+    assert!(output.status.success());
+    assert!(stdout(&output)
+        .contains("fn main() {\n    let change_observer = flowrs::node::ChangeObserver::new();"));
+
+    let output = run(&[
+        "--packages",
+        &packages,
+        "generate",
+        &fixture("flow.json"),
+        "--function",
+        "build",
+    ]);
+    assert!(output.status.success());
+    let stdout = stdout(&output);
+    assert!(stdout.contains(
+        "pub fn build(data: &serde_json::Value, change_observer: &flowrs::node::ChangeObserver) {"
+    ));
+    assert!(stdout.contains("let print = nodes::Print::<f32>::new();"));
+    assert!(
+        stdout.contains("flowrs::connection::connect(scale.output.clone(), print.input.clone());")
+    );
+
+    let output = run(&["emit", "nodes::Scale"]);
+    assert_eq!(Some(2), output.status.code());
+}
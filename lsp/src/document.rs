@@ -0,0 +1,333 @@
+use std::ops::Range;
+
+use flowrs_package::flow_package::package::Package;
+use lsp_types::Position;
+
+/// A value of a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// JSON pointer to the value, see RFC 6901.
+    pub pointer: String,
+    /// Byte range of the member name, including quotes, if the value is a member of an object.
+    pub key: Option<Range<usize>>,
+    /// Byte range of the value, including quotes or brackets.
+    pub value: Range<usize>,
+    /// Content of string values.
+    pub string: Option<String>,
+    /// Whether the value is an object or an array.
+    pub container: bool,
+}
+
+/// An open package file with the locations of its values.
+///
+/// The index is built by a tolerant parser: while the document is edited, the values up to the
+/// first syntax error are indexed, so completion works on incomplete documents.
+pub struct Document {
+    pub text: String,
+    pub entries: Vec<Entry>,
+    /// The deserialized package, or the reason why the document is none.
+    pub package: Result<Package, serde_json::Error>,
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let mut parser = Parser {
+            text: &text,
+            pos: 0,
+            entries: Vec::new(),
+        };
+        // Syntax errors are reported by serde below, the index keeps what was parsed before.
+        let _ = parser.value(String::new(), None);
+        let entries = parser.entries;
+        let package = serde_json::from_str(&text);
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            text,
+            entries,
+            package,
+            line_starts,
+        }
+    }
+
+    pub fn entry(&self, pointer: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.pointer == pointer)
+    }
+
+    /// The entry of the pointer or, if there is none, of its closest ancestor.
+    pub fn closest_entry(&self, pointer: &str) -> Option<&Entry> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(entry) = self.entry(pointer) {
+                return Some(entry);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+
+    /// The innermost entry whose member name or value contains the offset, and whether the
+    /// offset is within the member name.
+    pub fn entry_at(&self, offset: usize) -> Option<(&Entry, bool)> {
+        let inside = |range: &Range<usize>| range.start < offset && offset < range.end;
+        let mut found: Option<(&Entry, bool)> = None;
+        for entry in &self.entries {
+            let on_key = entry.key.as_ref().is_some_and(inside);
+            if !on_key && !inside(&entry.value) {
+                continue;
+            }
+            let len = |(e, k): (&Entry, bool)| match (k, &e.key) {
+                (true, Some(key)) => key.len(),
+                _ => e.value.len(),
+            };
+            if found.is_none_or(|f| len((entry, on_key)) <= len(f)) {
+                found = Some((entry, on_key));
+            }
+        }
+        found
+    }
+
+    /// Range the location of a pointer is shown at: the member name of objects and arrays, the
+    /// value otherwise.
+    pub fn pointer_range(&self, pointer: &str) -> Option<lsp_types::Range> {
+        let entry = self.closest_entry(pointer)?;
+        let range = match &entry.key {
+            Some(key) if entry.container => key.clone(),
+            _ => entry.value.clone(),
+        };
+        Some(self.range(&range))
+    }
+
+    /// Range of the member name a pointer is declared with, the value if it has none.
+    pub fn declaration_range(&self, pointer: &str) -> Option<lsp_types::Range> {
+        let entry = self.entry(pointer)?;
+        Some(self.range(entry.key.as_ref().unwrap_or(&entry.value)))
+    }
+
+    pub fn range(&self, range: &Range<usize>) -> lsp_types::Range {
+        lsp_types::Range::new(self.position(range.start), self.position(range.end))
+    }
+
+    /// Converts a byte offset to a position counting UTF-16 code units, as LSP does by default.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&s| s <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut character = 0;
+        for (i, c) in self.text[start..].char_indices() {
+            if character >= position.character as usize || c == '\n' {
+                return start + i;
+            }
+            character += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    /// Offset of a 1-based line and column as reported by serde_json.
+    pub fn line_column_offset(&self, line: usize, column: usize) -> usize {
+        let start = self
+            .line_starts
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or(self.text.len());
+        (start + column.saturating_sub(1)).min(self.text.len())
+    }
+}
+
+/// Escapes a reference token of a JSON pointer, see RFC 6901.
+pub fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    entries: Vec<Entry>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ()> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn value(&mut self, pointer: String, key: Option<Range<usize>>) -> Result<(), ()> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let index = self.entries.len();
+        self.entries.push(Entry {
+            pointer: pointer.clone(),
+            key,
+            value: start..start,
+            string: None,
+            container: matches!(self.peek(), Some(b'{' | b'[')),
+        });
+
+        let result = match self.peek() {
+            Some(b'{') => self.object(&pointer),
+            Some(b'[') => self.array(&pointer),
+            Some(b'"') => self.string().map(|s| self.entries[index].string = Some(s)),
+            Some(_) => {
+                while self.peek().is_some_and(|b| !b",}] \t\r\n".contains(&b)) {
+                    self.pos += 1;
+                }
+                if self.pos > start {
+                    Ok(())
+                } else {
+                    Err(())
+                }
+            }
+            None => Err(()),
+        };
+        self.entries[index].value = start..self.pos;
+        result
+    }
+
+    fn object(&mut self, pointer: &str) -> Result<(), ()> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(());
+            }
+            let key_start = self.pos;
+            let name = self.string()?;
+            let key = key_start..self.pos;
+            self.expect(b':')?;
+            self.value(format!("{}/{}", pointer, escape_pointer(&name)), Some(key))?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(()),
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: &str) -> Result<(), ()> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(());
+        }
+        for i in 0.. {
+            self.value(format!("{}/{}", pointer, i), None)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a string literal, the parser is at its opening quote.
+    fn string(&mut self) -> Result<String, ()> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.pos += 2,
+                Some(b'\n') | None => return Err(()),
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        serde_json::from_str(&self.text[start..self.pos]).map_err(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_test() {
+        let text =
+            "{\n  \"name\": \"nödes\",\n  \"a/b\": [1, {\"x\": \"y\"}],\n  \"broken\": {\"z\": ";
+        let document = Document::new(text.to_string());
+        assert!(document.package.is_err());
+
+        let pointers: Vec<&str> = document
+            .entries
+            .iter()
+            .map(|e| e.pointer.as_str())
+            .collect();
+        assert_eq!(
+            vec![
+                "",
+                "/name",
+                "/a~1b",
+                "/a~1b/0",
+                "/a~1b/1",
+                "/a~1b/1/x",
+                "/broken",
+                "/broken/z"
+            ],
+            pointers
+        );
+
+        let name = document.entry("/name").unwrap();
+        assert_eq!(Some("nödes".to_string()), name.string);
+        assert_eq!("\"name\"", &text[name.key.clone().unwrap()]);
+        assert_eq!(
+            lsp_types::Range::new(Position::new(1, 10), Position::new(1, 17)),
+            document.range(&name.value)
+        );
+        assert_eq!(name.value.start + 4, document.offset(Position::new(1, 13)));
+
+        let offset = text.find("\"y\"").unwrap() + 1;
+        let (entry, on_key) = document.entry_at(offset).unwrap();
+        assert_eq!(("/a~1b/1/x", false), (entry.pointer.as_str(), on_key));
+        let (entry, on_key) = document.entry_at(text.find("a/b").unwrap()).unwrap();
+        assert_eq!(("/a~1b", true), (entry.pointer.as_str(), on_key));
+
+        assert_eq!(
+            Some(Position::new(2, 2)),
+            document.pointer_range("/a~1b/7").map(|r| r.start)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! `flowrs-package-lsp`: a language server for flow-package JSON files, talking LSP on stdio.
+//!
+//! It reports the diagnostics of `Package::validate`, completes type names in type
+//! descriptions and constructor names of arguments, shows the ports and constructors of types
+//! on hover and jumps to the declaration of types and constructors in any package of the folder.
+
+mod document;
+mod workspace;
+
+use std::path::PathBuf;
+
+use anyhow::Error;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidChangeWatchedFiles, DidCloseTextDocument, DidOpenTextDocument,
+    DidSaveTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionOptions, CompletionResponse, DidChangeTextDocumentParams,
+    DidChangeWatchedFilesParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, GotoDefinitionResponse, HoverProviderCapability, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Url,
+};
+use serde_json::Value;
+
+use workspace::Workspace;
+
+fn main() -> Result<(), Error> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["\"".to_string(), ":".to_string()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+    serve(&connection)?;
+    // The writer thread ends once the connection is gone.
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn serve(connection: &Connection) -> Result<(), Error> {
+    let mut workspace = Workspace::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = handle_request(&workspace, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                handle_notification(connection, &mut workspace, notification)?;
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(workspace: &Workspace, request: Request) -> Response {
+    let result = match request.method.as_str() {
+        Completion::METHOD => dispatch::<Completion>(request.params, |params| {
+            let position = params.text_document_position;
+            Ok(workspace
+                .completion(&file_path(&position.text_document.uri)?, position.position)
+                .map(CompletionResponse::Array))
+        }),
+        HoverRequest::METHOD => dispatch::<HoverRequest>(request.params, |params| {
+            let position = params.text_document_position_params;
+            Ok(workspace.hover(&file_path(&position.text_document.uri)?, position.position))
+        }),
+        GotoDefinition::METHOD => dispatch::<GotoDefinition>(request.params, |params| {
+            let position = params.text_document_position_params;
+            Ok(workspace
+                .definition(&file_path(&position.text_document.uri)?, position.position)
+                .map(GotoDefinitionResponse::Scalar))
+        }),
+        method => {
+            return Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Method '{}' is not supported.", method),
+            )
+        }
+    };
+    match result {
+        Ok(value) => Response::new_ok(request.id, value),
+        Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn dispatch<R: lsp_types::request::Request>(
+    params: Value,
+    handler: impl FnOnce(R::Params) -> Result<R::Result, Error>,
+) -> Result<Value, Error> {
+    let result = handler(serde_json::from_value(params)?)?;
+    Ok(serde_json::to_value(result)?)
+}
+
+fn handle_notification(
+    connection: &Connection,
+    workspace: &mut Workspace,
+    notification: Notification,
+) -> Result<(), Error> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+            let document = params.text_document;
+            workspace.open(file_path(&document.uri)?, document.text);
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+            // The documents are synchronized in full, the last change is the whole text.
+            if let Some(change) = params.content_changes.into_iter().last() {
+                workspace.open(file_path(&params.text_document.uri)?, change.text);
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+            workspace.close(&file_path(&params.text_document.uri)?);
+            publish(connection, params.text_document.uri, Vec::new())?;
+        }
+        // Open documents depend on the files of their folder.
+        DidSaveTextDocument::METHOD => {
+            let params: DidSaveTextDocumentParams = serde_json::from_value(notification.params)?;
+            workspace.file_changed(&file_path(&params.text_document.uri)?);
+        }
+        DidChangeWatchedFiles::METHOD => {
+            let params: DidChangeWatchedFilesParams = serde_json::from_value(notification.params)?;
+            for change in params.changes {
+                workspace.file_changed(&file_path(&change.uri)?);
+            }
+        }
+        _ => return Ok(()),
+    }
+
+    // A change of one package can affect the diagnostics of every package in its folder.
+    for path in workspace.paths() {
+        let uri = Url::from_file_path(path)
+            .map_err(|_| Error::msg(format!("'{}' is no absolute path.", path.display())))?;
+        publish(connection, uri, workspace.diagnostics(path))?;
+    }
+    Ok(())
+}
+
+fn publish(
+    connection: &Connection,
+    uri: Url,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+) -> Result<(), Error> {
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            params,
+        )))?;
+    Ok(())
+}
+
+fn file_path(uri: &Url) -> Result<PathBuf, Error> {
+    uri.to_file_path()
+        .map_err(|_| Error::msg(format!("'{}' is no file.", uri)))
+}
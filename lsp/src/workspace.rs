@@ -0,0 +1,662 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+
+use flowrs_package::flow_package::package::{Package, Type, TypeDescription};
+use flowrs_package::flow_package::package_manager::{split_qualified_type_name, PackageManager};
+use flowrs_package::flow_package::validate::Severity;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Diagnostic, DiagnosticSeverity, Hover,
+    HoverContents, Location, MarkupContent, MarkupKind, Position, TextEdit, Url,
+};
+
+use crate::document::{escape_pointer, Document};
+
+/// The open package documents.
+///
+/// Packages are looked up in the folder of the document they are used in. Open documents take
+/// precedence over the files on disk. The packages of a folder are read once and kept until a
+/// document of the folder changes or a file of it is saved or changed on disk.
+#[derive(Default)]
+pub struct Workspace {
+    documents: HashMap<PathBuf, Document>,
+    /// The siblings of the documents, by the path of the document.
+    siblings: RefCell<HashMap<PathBuf, Rc<Siblings>>>,
+}
+
+/// What the string under the cursor refers to.
+#[derive(Debug, PartialEq)]
+enum Reference {
+    /// A type name in a type description, e.g. the `a::B` of `"Vec<a::B>"`.
+    Type {
+        name: String,
+        range: Range<usize>,
+        /// JSON pointer to the type the description belongs to, if any.
+        owner: Option<String>,
+    },
+    /// The constructor an argument is created with.
+    Constructor {
+        /// JSON pointer to the argument.
+        argument: String,
+        name: String,
+        range: Range<usize>,
+    },
+    /// The name of a type declared by the package.
+    Declaration { pointer: String },
+}
+
+/// The packages a document is analysed with.
+struct Context {
+    /// The package of the document. While the document cannot be deserialized, the file saved
+    /// last is used.
+    package: Option<Package>,
+    siblings: Rc<Siblings>,
+}
+
+/// The other packages of a document's folder.
+struct Siblings {
+    /// The packages with their files.
+    packages: Vec<(PathBuf, Package)>,
+    /// Knows the built-in package and the siblings, but not the package of the document.
+    pack_man: PackageManager,
+}
+
+impl Workspace {
+    pub fn open(&mut self, path: PathBuf, text: String) {
+        self.file_changed(&path);
+        self.documents.insert(path, Document::new(text));
+    }
+
+    pub fn close(&mut self, path: &Path) {
+        self.file_changed(path);
+        self.documents.remove(path);
+    }
+
+    /// Reads the packages of the folder of `path` again when they are needed next.
+    pub fn file_changed(&mut self, path: &Path) {
+        let folder = path.parent();
+        self.siblings
+            .get_mut()
+            .retain(|document, _| document.parent() != folder);
+    }
+
+    /// Paths of the open documents, sorted.
+    pub fn paths(&self) -> Vec<&PathBuf> {
+        let mut paths: Vec<&PathBuf> = self.documents.keys().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Syntax errors or, if there are none, the diagnostics of [`Package::validate`].
+    pub fn diagnostics(&self, path: &Path) -> Vec<Diagnostic> {
+        let Some(document) = self.documents.get(path) else {
+            return Vec::new();
+        };
+        let package = match &document.package {
+            Ok(package) => package,
+            Err(e) => {
+                let offset = document.line_column_offset(e.line(), e.column());
+                return vec![diagnostic(
+                    document.range(&(offset..offset)),
+                    DiagnosticSeverity::ERROR,
+                    e.to_string(),
+                )];
+            }
+        };
+
+        let context = self.context(path);
+        package
+            .validate(&context.siblings.pack_man)
+            .into_iter()
+            .map(|d| {
+                diagnostic(
+                    document.pointer_range(&d.pointer).unwrap_or_default(),
+                    match d.severity {
+                        Severity::Error => DiagnosticSeverity::ERROR,
+                        Severity::Warning => DiagnosticSeverity::WARNING,
+                    },
+                    d.message,
+                )
+            })
+            .collect()
+    }
+
+    /// Type names in type descriptions and constructor names of arguments.
+    pub fn completion(&self, path: &Path, position: Position) -> Option<Vec<CompletionItem>> {
+        let document = self.documents.get(path)?;
+        let context = self.context(path);
+        let item = |label: &str, kind, detail: String, range: &Range<usize>| CompletionItem {
+            label: label.to_string(),
+            kind: Some(kind),
+            detail: Some(detail),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                document.range(range),
+                label.to_string(),
+            ))),
+            ..Default::default()
+        };
+
+        let mut items = Vec::new();
+        match reference_at(document, document.offset(position))? {
+            Reference::Type { range, owner, .. } => {
+                if let Some((_, type_desc)) = owner
+                    .as_deref()
+                    .and_then(|owner| context.declared_type(owner))
+                {
+                    for tp in type_desc.type_parameters.iter().flatten() {
+                        items.push(item(
+                            &tp.name,
+                            CompletionItemKind::TYPE_PARAMETER,
+                            "type parameter".to_string(),
+                            &range,
+                        ));
+                    }
+                }
+                let built_in = context.siblings.pack_man.get_package("built-in")?;
+                let mut primitives: Vec<&String> = built_in
+                    .crates
+                    .get("primitives")
+                    .map(|c| c.types.keys().collect())
+                    .unwrap_or_default();
+                primitives.sort();
+                for name in primitives {
+                    items.push(item(
+                        name,
+                        CompletionItemKind::STRUCT,
+                        "built-in".to_string(),
+                        &range,
+                    ));
+                }
+                let packages = context
+                    .package
+                    .iter()
+                    .chain(context.siblings.packages.iter().map(|(_, p)| p));
+                for package in packages {
+                    for (type_path, _) in package.types() {
+                        items.push(item(
+                            &type_path,
+                            CompletionItemKind::STRUCT,
+                            format!("{}@{}", package.name, package.version),
+                            &range,
+                        ));
+                    }
+                }
+            }
+            Reference::Constructor {
+                argument, range, ..
+            } => {
+                let (_, _, type_desc) = context.resolve(&argument_type(document, &argument)?)?;
+                let mut constructors: Vec<&String> = type_desc.constructors.keys().collect();
+                constructors.sort();
+                for name in constructors {
+                    items.push(item(
+                        name,
+                        CompletionItemKind::CONSTRUCTOR,
+                        "constructor".to_string(),
+                        &range,
+                    ));
+                }
+            }
+            Reference::Declaration { .. } => {}
+        }
+        Some(items)
+    }
+
+    /// Ports, type parameters and constructors of the type under the cursor.
+    pub fn hover(&self, path: &Path, position: Position) -> Option<Hover> {
+        let document = self.documents.get(path)?;
+        let context = self.context(path);
+        let (value, range) = match reference_at(document, document.offset(position))? {
+            Reference::Type { name, range, .. } => {
+                let (package, type_path, type_desc) = context.resolve(&name)?;
+                (describe_type(&type_path, package, type_desc), range)
+            }
+            Reference::Declaration { pointer } => {
+                let (type_path, type_desc) = context.declared_type(&pointer)?;
+                let range = document.entry(&pointer)?.key.clone()?;
+                (
+                    describe_type(&type_path, context.package.as_ref()?, type_desc),
+                    range,
+                )
+            }
+            Reference::Constructor { .. } => return None,
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(document.range(&range)),
+        })
+    }
+
+    /// Where the type or constructor under the cursor is declared, in any package of the folder.
+    pub fn definition(&self, path: &Path, position: Position) -> Option<Location> {
+        let document = self.documents.get(path)?;
+        let context = self.context(path);
+        let (package, pointer) = match reference_at(document, document.offset(position))? {
+            Reference::Type { name, .. } => {
+                let (package, type_path, _) = context.resolve(&name)?;
+                (package, package.type_pointer(&type_path)?)
+            }
+            Reference::Constructor { argument, name, .. } => {
+                let (package, type_path, type_desc) =
+                    context.resolve(&argument_type(document, &argument)?)?;
+                if !type_desc.constructors.contains_key(&name) {
+                    return None;
+                }
+                let pointer = format!(
+                    "{}/constructors/{}",
+                    package.type_pointer(&type_path)?,
+                    escape_pointer(&name)
+                );
+                (package, pointer)
+            }
+            Reference::Declaration { .. } => return None,
+        };
+
+        let file = context.file(path, package)?;
+        let range = match self.documents.get(&file) {
+            Some(target) => target.declaration_range(&pointer)?,
+            None => Document::new(fs::read_to_string(&file).ok()?).declaration_range(&pointer)?,
+        };
+        Some(Location::new(Url::from_file_path(&file).ok()?, range))
+    }
+
+    fn context(&self, path: &Path) -> Context {
+        let package = match self.documents.get(path).map(|d| &d.package) {
+            Some(Ok(package)) => Some(package.clone()),
+            _ => PackageManager::load_package_file(path).ok(),
+        };
+        let siblings = self
+            .siblings
+            .borrow_mut()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Rc::new(self.read_siblings(path)))
+            .clone();
+        Context { package, siblings }
+    }
+
+    fn read_siblings(&self, path: &Path) -> Siblings {
+        let folder = path.parent().unwrap_or(Path::new("."));
+        let mut paths: Vec<PathBuf> = fs::read_dir(folder)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .chain(self.documents.keys().cloned())
+            .filter(|p| p.parent() == Some(folder) && p != path)
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        let packages: Vec<(PathBuf, Package)> = paths
+            .into_iter()
+            .filter_map(|p| {
+                let package = match self.documents.get(&p) {
+                    Some(document) => document.package.as_ref().ok().cloned(),
+                    None => PackageManager::load_package_file(&p).ok(),
+                }?;
+                Some((p, package))
+            })
+            .collect();
+
+        let mut pack_man = PackageManager::new();
+        for (_, sibling) in &packages {
+            // Packages that cannot be registered are reported in their own documents.
+            let _ = pack_man.add_package(sibling.clone());
+        }
+
+        Siblings { packages, pack_man }
+    }
+}
+
+impl Context {
+    /// Resolves a type name in the package of the document first, then like
+    /// [`PackageManager::resolve_type`]. Returns the package and the path of the type.
+    fn resolve(&self, type_name: &str) -> Option<(&Package, String, &Type)> {
+        let (package_name, type_path) = split_qualified_type_name(type_name);
+        if let Some(package) = &self.package {
+            if package_name.is_none_or(|name| name == package.name) {
+                let type_ids: Vec<&str> = type_path.split("::").collect();
+                if let Some(type_desc) = self
+                    .siblings
+                    .pack_man
+                    .get_type_from_package(&type_ids, package)
+                {
+                    return Some((package, type_path.to_string(), type_desc));
+                }
+            }
+        }
+        let resolved = self.siblings.pack_man.resolve_type(type_name).ok()?;
+        Some((resolved.package, type_path.to_string(), resolved.type_desc))
+    }
+
+    /// The type of the document's package declared at a JSON pointer, with its path.
+    fn declared_type(&self, pointer: &str) -> Option<(String, &Type)> {
+        let package = self.package.as_ref()?;
+        package
+            .types()
+            .into_iter()
+            .find(|(type_path, _)| package.type_pointer(type_path).as_deref() == Some(pointer))
+    }
+
+    /// The file a package was read from, `None` for the built-in package.
+    fn file(&self, path: &Path, package: &Package) -> Option<PathBuf> {
+        let same = |p: &Package| p.name == package.name && p.version == package.version;
+        if self.package.as_ref().is_some_and(same) {
+            return Some(path.to_path_buf());
+        }
+        self.siblings
+            .packages
+            .iter()
+            .find(|(_, sibling)| same(sibling))
+            .map(|(file, _)| file.clone())
+    }
+}
+
+fn diagnostic(
+    range: lsp_types::Range,
+    severity: DiagnosticSeverity,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("flowrs-package".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn reference_at(document: &Document, offset: usize) -> Option<Reference> {
+    let (entry, on_key) = document.entry_at(offset)?;
+    let segments: Vec<&str> = entry.pointer.split('/').collect();
+    if on_key {
+        return (segments.len() >= 2 && segments[segments.len() - 2] == "types").then(|| {
+            Reference::Declaration {
+                pointer: entry.pointer.clone(),
+            }
+        });
+    }
+
+    let string = entry.string.as_ref()?;
+    let content = entry.value.start + 1..entry.value.end - 1;
+    if segments.ends_with(&["construction", "Constructor"]) {
+        return Some(Reference::Constructor {
+            argument: segments[..segments.len() - 2].join("/"),
+            name: string.clone(),
+            range: content,
+        });
+    }
+    if !is_type_description(&segments) {
+        return None;
+    }
+
+    // The path under the cursor, descriptions in Rust syntax may contain several.
+    let is_path = |c: char| c.is_alphanumeric() || matches!(c, '_' | ':' | '/');
+    let text = &document.text;
+    let start = text[content.start..offset]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_path(*c))
+        .map_or(content.start, |(i, c)| content.start + i + c.len_utf8());
+    let end = text[offset..content.end]
+        .find(|c| !is_path(c))
+        .map_or(content.end, |i| offset + i);
+    let owner = segments
+        .iter()
+        .rposition(|s| *s == "types")
+        .filter(|i| i + 1 < segments.len())
+        .map(|i| segments[..i + 2].join("/"));
+    Some(Reference::Type {
+        name: text[start..end].to_string(),
+        range: start..end,
+        owner,
+    })
+}
+
+/// Whether the segments of a JSON pointer lead to a type description of a port, an argument or
+/// a context object, or to the name of a type within one written as object.
+fn is_type_description(segments: &[&str]) -> bool {
+    let Some(i) = segments.iter().rposition(|s| *s == "type") else {
+        return false;
+    };
+    i >= 2
+        && ["inputs", "outputs", "arguments", "context_objects"].contains(&segments[i - 2])
+        && match &segments[i + 1..] {
+            [] => true,
+            [.., kind, "name"] => *kind == "Type" || *kind == "Generic",
+            _ => false,
+        }
+}
+
+/// The name of an argument's type, if it is written in Rust syntax or as `Type` object.
+fn argument_type(document: &Document, argument: &str) -> Option<String> {
+    if let Some(name) = document
+        .entry(&format!("{}/type/Type/name", argument))
+        .and_then(|e| e.string.clone())
+    {
+        return Some(name);
+    }
+    let description = document
+        .entry(&format!("{}/type", argument))?
+        .string
+        .as_ref()?;
+    match TypeDescription::from_str(description).ok()? {
+        TypeDescription::Type { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+fn describe_type(type_path: &str, package: &Package, type_desc: &Type) -> String {
+    let mut lines = vec![format!(
+        "**`{}`** from `{}@{}`",
+        type_path, package.name, package.version
+    )];
+    let code_list = |items: Vec<String>| {
+        items
+            .iter()
+            .map(|i| format!("`{}`", i))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    if let Some(type_parameters) = &type_desc.type_parameters {
+        let parameters = type_parameters
+            .iter()
+            .map(|tp| match tp.constraints.is_empty() {
+                true => tp.name.clone(),
+                false => format!("{}: {}", tp.name, tp.constraints.join(" + ")),
+            })
+            .collect();
+        lines.push(format!("Type parameters: {}", code_list(parameters)));
+    }
+    if let Some(inputs) = &type_desc.inputs {
+        let mut ports: Vec<String> = inputs
+            .iter()
+            .map(|(name, input)| format!("{}: {}", name, input.input_type))
+            .collect();
+        ports.sort();
+        lines.push(format!("Inputs: {}", code_list(ports)));
+    }
+    if let Some(outputs) = &type_desc.outputs {
+        let mut ports: Vec<String> = outputs
+            .iter()
+            .map(|(name, output)| format!("{}: {}", name, output.output_type))
+            .collect();
+        ports.sort();
+        lines.push(format!("Outputs: {}", code_list(ports)));
+    }
+    let mut constructors: Vec<String> = type_desc.constructors.keys().cloned().collect();
+    constructors.sort();
+    lines.push(format!("Constructors: {}", code_list(constructors)));
+    lines.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODES_JSON: &str = r#"{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+        "nodes": {
+            "types": {
+                "Scale": {
+                    "inputs": {"input": {"type": "T"}},
+                    "outputs": {"output": {"type": "math::Factor"}},
+                    "type_parameters": [{"name": "T", "where": ["Copy"]}],
+                    "constructors": {"New": {"NewWithArbitraryArgs": {
+                        "function_name": "new",
+                        "arguments": [
+                            {"type": "math::Factor", "name": "factor", "passing": "Move", "construction": {"Constructor": "FromJson"}}
+                        ]
+                    }}}
+                }
+            },
+            "modules": {}
+        }
+    }
+}"#;
+
+    const MATH_JSON: &str = r#"{
+    "name": "math",
+    "version": "0.1.0",
+    "crates": {
+        "math": {
+            "types": {
+                "Factor": {
+                    "inputs": null,
+                    "outputs": null,
+                    "type_parameters": null,
+                    "constructors": {"FromJson": "FromJson", "Unit": {"New": {"function_name": "unit"}}}
+                }
+            },
+            "modules": {}
+        }
+    }
+}"#;
+
+    fn position_of(text: &str, pattern: &str, delta: usize) -> Position {
+        Document::new(text.to_string()).position(text.find(pattern).unwrap() + delta)
+    }
+
+    #[test]
+    fn workspace_test() {
+        let folder = PathBuf::from("/flowrs-package-lsp-test");
+        let nodes = folder.join("nodes.json");
+        let math = folder.join("math.json");
+        let mut workspace = Workspace::default();
+        workspace.open(nodes.clone(), NODES_JSON.to_string());
+        workspace.open(math.clone(), MATH_JSON.to_string());
+
+        assert_eq!(Vec::<Diagnostic>::new(), workspace.diagnostics(&nodes));
+        workspace.open(
+            nodes.clone(),
+            NODES_JSON.replace("\"FromJson\"}", "\"Missing\"}"),
+        );
+        let diagnostics = workspace.diagnostics(&nodes);
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            "Type 'math::Factor' has no constructor 'Missing'.",
+            diagnostics[0].message
+        );
+        assert_eq!(
+            position_of(NODES_JSON, "\"FromJson\"}", 0),
+            diagnostics[0].range.start
+        );
+        workspace.open(nodes.clone(), NODES_JSON.replace("\"1.0.0\",", "\"1.0.0\""));
+        assert_eq!(
+            Some(DiagnosticSeverity::ERROR),
+            workspace.diagnostics(&nodes)[0].severity
+        );
+        workspace.open(nodes.clone(), NODES_JSON.to_string());
+
+        let labels = |items: Option<Vec<CompletionItem>>| -> Vec<String> {
+            items.unwrap().into_iter().map(|i| i.label).collect()
+        };
+        let items = workspace.completion(&nodes, position_of(NODES_JSON, "\"T\"}", 1));
+        let labels_1 = labels(items);
+        assert_eq!("T", labels_1[0]);
+        assert!(labels_1.contains(&"f32".to_string()));
+        assert!(labels_1.contains(&"nodes::Scale".to_string()));
+        assert!(labels_1.contains(&"math::Factor".to_string()));
+        let items = workspace.completion(&nodes, position_of(NODES_JSON, "FromJson\"}", 1));
+        assert_eq!(vec!["FromJson", "Unit"], labels(items));
+        assert_eq!(
+            None,
+            workspace.completion(&nodes, position_of(NODES_JSON, "function_name", 1))
+        );
+
+        let hover = workspace
+            .hover(&nodes, position_of(NODES_JSON, "math::Factor", 2))
+            .unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("hover is no markup.");
+        };
+        assert_eq!(
+            "**`math::Factor`** from `math@0.1.0`\n\nConstructors: `FromJson`, `Unit`",
+            markup.value
+        );
+        let hover = workspace
+            .hover(&nodes, position_of(NODES_JSON, "\"Scale\"", 1))
+            .unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("hover is no markup.");
+        };
+        assert_eq!(
+            "**`nodes::Scale`** from `nodes@1.0.0`\n\n\
+             Type parameters: `T: Copy`\n\n\
+             Inputs: `input: T`\n\n\
+             Outputs: `output: math::Factor`\n\n\
+             Constructors: `New`",
+            markup.value
+        );
+
+        let location = workspace
+            .definition(&nodes, position_of(NODES_JSON, "math::Factor", 8))
+            .unwrap();
+        assert_eq!(Url::from_file_path(&math).unwrap(), location.uri);
+        assert_eq!(
+            position_of(MATH_JSON, "\"Factor\"", 0),
+            location.range.start
+        );
+        let location = workspace
+            .definition(&nodes, position_of(NODES_JSON, "FromJson\"}", 0))
+            .unwrap();
+        assert_eq!(
+            position_of(MATH_JSON, "\"FromJson\": \"FromJson\"", 0),
+            location.range.start
+        );
+    }
+
+    #[test]
+    fn siblings_cache_test() {
+        let folder =
+            std::env::temp_dir().join(format!("flowrs_package_lsp_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+        let nodes = folder.join("nodes.json");
+        let math = folder.join("math.json");
+        fs::write(&math, MATH_JSON).unwrap();
+        let mut workspace = Workspace::default();
+        workspace.open(nodes.clone(), NODES_JSON.to_string());
+        assert_eq!(Vec::<Diagnostic>::new(), workspace.diagnostics(&nodes));
+
+        // Files on disk are read again once they are reported as changed.
+        fs::write(&math, MATH_JSON.replace("\"FromJson\": \"FromJson\", ", "")).unwrap();
+        assert_eq!(Vec::<Diagnostic>::new(), workspace.diagnostics(&nodes));
+        workspace.file_changed(&math);
+        assert_eq!(
+            "Type 'math::Factor' has no constructor 'FromJson'.",
+            workspace.diagnostics(&nodes)[0].message
+        );
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
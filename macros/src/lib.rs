@@ -0,0 +1,178 @@
+//! `flow!` expands a flow description into the code `Flow::emit_tokens` generates for it.
+//!
+//! ```ignore
+//! flow!("flows/scale.json", packages = "packages/");
+//! flow!(
+//!     "flows/scale.json",
+//!     packages = "packages/",
+//!     function = "build",
+//!     parameters = ["change_observer: &ChangeObserver"],
+//!     error_type = "Box<dyn std::error::Error>"
+//! );
+//! ```
+//!
+//! Paths are relative to the directory of the calling crate's `Cargo.toml`. Without `function`
+//! the flow becomes `fn main()` with the flow's data embedded, otherwise a library function the
+//! data is passed to.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use flowrs_package::flow_package::flow::{Flow, FlowCodeOptions, FlowTarget};
+use flowrs_package::flow_package::package_manager::{LoadMode, PackageManager};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, parse_macro_input, Ident, LitStr, Token};
+
+#[proc_macro]
+pub fn flow(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as FlowInput);
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+struct FlowInput {
+    flow: LitStr,
+    packages: LitStr,
+    function: Option<LitStr>,
+    parameters: Vec<LitStr>,
+    prelude: Vec<LitStr>,
+    connect: Option<LitStr>,
+    add_node: Option<LitStr>,
+    error_type: Option<LitStr>,
+}
+
+impl Parse for FlowInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let flow: LitStr = input.parse()?;
+        let mut packages = None;
+        let mut result = Self {
+            packages: flow.clone(),
+            flow,
+            function: None,
+            parameters: Vec::new(),
+            prelude: Vec::new(),
+            connect: None,
+            add_node: None,
+            error_type: None,
+        };
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "packages" => packages = Some(input.parse()?),
+                "function" => result.function = Some(input.parse()?),
+                "parameters" => result.parameters = parse_list(input)?,
+                "prelude" => result.prelude = parse_list(input)?,
+                "connect" => result.connect = Some(input.parse()?),
+                "add_node" => result.add_node = Some(input.parse()?),
+                "error_type" => result.error_type = Some(input.parse()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("Unknown option '{}'.", key),
+                    ))
+                }
+            }
+        }
+
+        result.packages = packages.ok_or_else(|| {
+            syn::Error::new(
+                result.flow.span(),
+                "Missing package folder: `packages = \"...\"`.",
+            )
+        })?;
+        Ok(result)
+    }
+}
+
+fn parse_list(input: ParseStream) -> syn::Result<Vec<LitStr>> {
+    let content;
+    bracketed!(content in input);
+    Ok(content
+        .parse_terminated(|p| p.parse::<LitStr>(), Token![,])?
+        .into_iter()
+        .collect())
+}
+
+fn expand(input: &FlowInput) -> syn::Result<TokenStream> {
+    let flow_path = manifest_relative(&input.flow);
+    let packages_path = manifest_relative(&input.packages);
+
+    let (pm, report) =
+        PackageManager::load_from_folder(&packages_path.to_string_lossy(), LoadMode::Lenient)
+            .map_err(|e| syn::Error::new(input.packages.span(), e.to_string()))?;
+    if !report.is_ok() {
+        let errors: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        return Err(syn::Error::new(input.packages.span(), errors.join("\n")));
+    }
+
+    let json = fs::read_to_string(&flow_path).map_err(|e| {
+        syn::Error::new(
+            input.flow.span(),
+            format!("Could not read '{}': {}", flow_path.display(), e),
+        )
+    })?;
+    let flow: Flow = serde_json::from_str(&json).map_err(|e| {
+        syn::Error::new(
+            input.flow.span(),
+            format!("{}:{}:{}: {}", flow_path.display(), e.line(), e.column(), e),
+        )
+    })?;
+
+    let mut options = FlowCodeOptions {
+        target: match &input.function {
+            Some(name) => FlowTarget::Function {
+                name: name.value(),
+                parameters: input.parameters.iter().map(|p| p.value()).collect(),
+            },
+            None => FlowTarget::Main {
+                prelude: input.prelude.iter().map(|p| p.value()).collect(),
+            },
+        },
+        add_node_function: input.add_node.as_ref().map(|a| a.value()),
+        error_type: input.error_type.as_ref().map(|e| e.value()),
+        ..Default::default()
+    };
+    if let Some(connect) = &input.connect {
+        options.connect_function = connect.value();
+    }
+
+    let code = flow.emit_tokens(&pm, &options).map_err(|e| {
+        syn::Error::new(input.flow.span(), format!("{}: {}", flow_path.display(), e))
+    })?;
+
+    // Rebuild when the flow or a package changes.
+    let mut tracked = vec![flow_path];
+    tracked.extend(json_files(&packages_path));
+    let tracked = tracked.iter().map(|p| p.to_string_lossy().into_owned());
+
+    Ok(quote! {
+        #(const _: &[u8] = include_bytes!(#tracked);)*
+        #code
+    })
+}
+
+fn manifest_relative(path: &LitStr) -> PathBuf {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    Path::new(&root).join(path.value())
+}
+
+fn json_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .collect();
+    files.sort();
+    files
+}
@@ -0,0 +1,93 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use flowrs_package_macros::flow;
+
+mod nodes {
+    use std::marker::PhantomData;
+
+    use super::Edge;
+
+    pub struct Source<T> {
+        pub output: Edge,
+        value: PhantomData<T>,
+    }
+
+    impl<T> Source<T> {
+        pub fn new() -> Self {
+            Self {
+                output: Edge::default(),
+                value: PhantomData,
+            }
+        }
+    }
+
+    pub struct Scale {
+        pub input: Edge,
+    }
+
+    impl Scale {
+        pub fn with_factor(factor: f32) -> Result<Self, String> {
+            if factor > 0.0 {
+                Ok(Self {
+                    input: Edge::default(),
+                })
+            } else {
+                Err(format!("factor {} is not positive", factor))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Edge(Rc<RefCell<Option<Edge>>>);
+
+thread_local! {
+    static CONNECTIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn connect(output: Edge, input: Edge) {
+    *output.0.borrow_mut() = Some(input);
+    CONNECTIONS.with(|c| c.set(c.get() + 1));
+}
+
+flow!(
+    "tests/fixtures/scale.json",
+    packages = "tests/fixtures/packages",
+    function = "build",
+    connect = "connect",
+    error_type = "Box<dyn std::error::Error>",
+);
+
+mod embedded {
+    use super::{connect, flow, nodes};
+
+    flow!(
+        "tests/fixtures/scale.json",
+        packages = "tests/fixtures/packages",
+        connect = "connect"
+    );
+
+    pub fn run() {
+        main()
+    }
+}
+
+#[test]
+fn function_test() {
+    CONNECTIONS.with(|c| c.set(0));
+    build(&serde_json::json!({"scale": {"factor": 2.5}})).expect("flow failed.");
+    assert_eq!(1, CONNECTIONS.with(|c| c.get()));
+
+    let err = build(&serde_json::json!({"scale": {"factor": -1.0}})).unwrap_err();
+    assert_eq!("factor -1 is not positive", err.to_string());
+
+    assert!(build(&serde_json::json!({})).is_err());
+}
+
+#[test]
+fn main_test() {
+    CONNECTIONS.with(|c| c.set(0));
+    embedded::run();
+    assert_eq!(1, CONNECTIONS.with(|c| c.get()));
+}
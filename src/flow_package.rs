@@ -1,2 +1,15 @@
+pub mod data_schema;
+pub mod dependency;
+pub mod extract;
+pub mod flow;
+pub mod inference;
+pub mod manifest;
 pub mod package;
 pub mod package_manager;
+pub mod rustdoc;
+pub mod shared;
+pub mod templates;
+pub mod tokens;
+pub mod type_check;
+pub mod type_description;
+pub mod validate;
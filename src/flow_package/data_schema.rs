@@ -0,0 +1,253 @@
+use anyhow::Error;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use crate::flow::Flow;
+use crate::package::{Constructor, JsonRead, Namespace, ObjectDescription, TypeDescription};
+use crate::package_manager::PackageManager;
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl Constructor {
+    /// JSON Schema of the `data` document read by the code `emit_code_template` emits for the
+    /// object, e.g. `{"scale": {"factor": 2.5}}` for a `scale` whose `factor` is created from Json.
+    pub fn data_schema(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+    ) -> Result<Value, Error> {
+        let reads = self.json_reads(obj_desc, type_parameters, pack_man, &Namespace::new())?;
+        Ok(document_schema(&reads, pack_man))
+    }
+}
+
+impl Flow {
+    /// JSON Schema of the `data` document read by the code [`Flow::emit_code`] emits, i.e. of
+    /// [`Flow::data_document`].
+    pub fn data_schema(&self, pack_man: &PackageManager) -> Result<Value, Error> {
+        let reads = self.json_reads(pack_man)?;
+        Ok(document_schema(&reads, pack_man))
+    }
+}
+
+/// The keys of the document read by the generated code as tree.
+enum SchemaNode {
+    Value(Value),
+    Object(BTreeMap<String, SchemaNode>),
+}
+
+impl SchemaNode {
+    fn insert(&mut self, path: &[String], schema: Value) {
+        let SchemaNode::Object(properties) = self else {
+            // A value read as a whole already contains everything below it.
+            return;
+        };
+        match path {
+            [] => {}
+            [key] => {
+                properties.insert(key.clone(), SchemaNode::Value(schema));
+            }
+            [key, rest @ ..] => properties
+                .entry(key.clone())
+                .or_insert_with(|| SchemaNode::Object(BTreeMap::new()))
+                .insert(rest, schema),
+        }
+    }
+
+    fn into_schema(self) -> Value {
+        match self {
+            SchemaNode::Value(schema) => schema,
+            SchemaNode::Object(properties) => {
+                let required: Vec<&String> = properties.keys().collect();
+                let required = json!(required);
+                let properties: Map<String, Value> = properties
+                    .into_iter()
+                    .map(|(key, node)| (key, node.into_schema()))
+                    .collect();
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false
+                })
+            }
+        }
+    }
+}
+
+fn document_schema(reads: &[JsonRead], pack_man: &PackageManager) -> Value {
+    let mut root = SchemaNode::Object(BTreeMap::new());
+    for read in reads {
+        root.insert(&read.path, type_schema(&read.rust_type, pack_man));
+    }
+
+    let mut schema = root.into_schema();
+    schema["$schema"] = json!(SCHEMA_DIALECT);
+    schema
+}
+
+/// JSON Schema of the serde representation of a Rust type. Only built-in types and tuples and
+/// arrays of them are known, anything else is described by its type name only.
+pub fn type_schema(rust_type: &str, pack_man: &PackageManager) -> Value {
+    match TypeDescription::from_str(rust_type) {
+        Ok(td) => type_description_schema(&td, pack_man),
+        Err(_) => unknown_schema(rust_type),
+    }
+}
+
+fn type_description_schema(td: &TypeDescription, pack_man: &PackageManager) -> Value {
+    match td {
+        TypeDescription::Type {
+            name,
+            type_parameters: None,
+        } if pack_man.is_built_in_type(name) => primitive_schema(name),
+
+        TypeDescription::Tuple { elements } if elements.is_empty() => json!({"type": "null"}),
+        TypeDescription::Tuple { elements } => json!({
+            "type": "array",
+            "prefixItems": elements
+                .iter()
+                .map(|e| type_description_schema(e, pack_man))
+                .collect::<Vec<Value>>(),
+            "items": false,
+            "minItems": elements.len(),
+            "maxItems": elements.len()
+        }),
+
+        TypeDescription::Array { element, length } => json!({
+            "type": "array",
+            "items": type_description_schema(element, pack_man),
+            "minItems": length,
+            "maxItems": length
+        }),
+
+        _ => unknown_schema(&td.to_string()),
+    }
+}
+
+fn primitive_schema(name: &str) -> Value {
+    match name {
+        "bool" => json!({"type": "boolean"}),
+        "char" => json!({"type": "string", "minLength": 1, "maxLength": 1}),
+        "f32" | "f64" => json!({"type": "number"}),
+        "()" => json!({"type": "null"}),
+        "i8" => integer_schema(i8::MIN as i64, i8::MAX as i64),
+        "i16" => integer_schema(i16::MIN as i64, i16::MAX as i64),
+        "i32" => integer_schema(i32::MIN as i64, i32::MAX as i64),
+        "i64" | "isize" => integer_schema(i64::MIN, i64::MAX),
+        "u8" => integer_schema(0, u8::MAX as u64),
+        "u16" => integer_schema(0, u16::MAX as u64),
+        "u32" => integer_schema(0, u32::MAX as u64),
+        "u64" | "usize" => integer_schema(0, u64::MAX),
+        "u128" => json!({"type": "integer", "minimum": 0}),
+        _ => json!({"type": "integer"}),
+    }
+}
+
+fn integer_schema<T: Into<Value>>(minimum: T, maximum: T) -> Value {
+    json!({"type": "integer", "minimum": minimum.into(), "maximum": maximum.into()})
+}
+
+fn unknown_schema(rust_type: &str) -> Value {
+    json!({ "description": rust_type })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+    "nodes": {
+        "types": {
+        "Scale": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {"New": {"NewWithArbitraryArgs": {
+                "function_name": "new",
+                "arguments": [
+                    {"type": "T", "name": "factor", "passing": "Move", "construction": {"Constructor": "Json"}},
+                    {"type": "nodes::Range", "name": "range", "passing": "Move", "construction": {"Constructor": "New"}},
+                    {"type": "nodes::Params", "name": "params", "passing": "Move", "construction": {"Constructor": "Json"}},
+                    {"type": "u8", "name": "unread", "passing": "Move", "construction": {"Constructor": "Default"}}
+                ]
+            }}}
+        },
+        "Range": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"New": {"NewWithArbitraryArgs": {
+                "function_name": "new",
+                "arguments": [
+                    {"type": "(u8, char)", "name": "bounds", "passing": "Move", "construction": {"Constructor": "Json"}}
+                ]
+            }}}
+        },
+        "Params": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"Json": "FromJson"}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    #[test]
+    fn data_schema_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        let flow: Flow = serde_json::from_str(
+            r#"{"nodes": {"scale": {"type": "nodes::Scale", "constructor": "New", "type_parameters": {"T": "f32"}}}}"#,
+        )
+        .expect("wrong format.");
+
+        let expected = json!({
+            "$schema": SCHEMA_DIALECT,
+            "type": "object",
+            "properties": {
+                "scale": {
+                    "type": "object",
+                    "properties": {
+                        "factor": {"type": "number"},
+                        "params": {"description": "nodes::Params"},
+                        "range": {
+                            "type": "object",
+                            "properties": {
+                                "bounds": {
+                                    "type": "array",
+                                    "prefixItems": [
+                                        {"type": "integer", "minimum": 0, "maximum": 255},
+                                        {"type": "string", "minLength": 1, "maxLength": 1}
+                                    ],
+                                    "items": false,
+                                    "minItems": 2,
+                                    "maxItems": 2
+                                }
+                            },
+                            "required": ["bounds"],
+                            "additionalProperties": false
+                        }
+                    },
+                    "required": ["factor", "params", "range"],
+                    "additionalProperties": false
+                }
+            },
+            "required": ["scale"],
+            "additionalProperties": false
+        });
+        assert_eq!(expected, flow.data_schema(&pm).unwrap());
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use semver::{Version, VersionReq};
+
+use crate::package::Package;
+use crate::package_manager::PackageManager;
+
+/// A package that was pulled into a resolution, together with the package that required it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedDependency {
+    pub name: String,
+    pub version: Version,
+    /// `None` for the package the resolution was started from.
+    pub required_by: Option<(String, Version)>,
+    pub requirement: VersionReq,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyError {
+    InvalidRequirement {
+        package: String,
+        dependency: String,
+        requirement: String,
+        reason: String,
+    },
+    Missing {
+        dependency: String,
+        requirement: VersionReq,
+        /// Packages (`name@version`) from the root to the package declaring the dependency.
+        chain: Vec<String>,
+    },
+    Cycle {
+        /// Packages (`name@version`) forming the cycle, the first one is repeated at the end.
+        chain: Vec<String>,
+    },
+}
+
+impl fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyError::InvalidRequirement {
+                package,
+                dependency,
+                requirement,
+                reason,
+            } => write!(
+                f,
+                "Package '{}' declares invalid requirement '{}' for '{}': {}",
+                package, requirement, dependency, reason
+            ),
+            DependencyError::Missing {
+                dependency,
+                requirement,
+                chain,
+            } => write!(
+                f,
+                "No version of package '{}' matches '{}' (required by {}).",
+                dependency,
+                requirement,
+                chain.join(" -> ")
+            ),
+            DependencyError::Cycle { chain } => {
+                write!(f, "Cyclic package dependency: {}.", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+fn package_id(package: &Package) -> String {
+    format!("{}@{}", package.name, package.version)
+}
+
+impl PackageManager {
+    /// Resolves the dependency graph of the newest version of a package matching `requirement`.
+    ///
+    /// Each dependency resolves to the newest registered version satisfying its requirement.
+    /// The result is in dependency order: every package comes after all packages it depends on,
+    /// the root package is last.
+    pub fn resolve_dependencies(
+        &self,
+        package_name: &str,
+        requirement: &VersionReq,
+    ) -> Result<Vec<ResolvedDependency>, DependencyError> {
+        let root = self
+            .get_package_matching(package_name, requirement)
+            .ok_or_else(|| DependencyError::Missing {
+                dependency: package_name.to_string(),
+                requirement: requirement.clone(),
+                chain: Vec::new(),
+            })?;
+
+        let mut resolved = Vec::new();
+        let mut done = HashSet::new();
+        let mut stack = Vec::new();
+        self.resolve_rec(
+            root,
+            None,
+            requirement,
+            &mut stack,
+            &mut done,
+            &mut resolved,
+        )?;
+
+        Ok(resolved)
+    }
+
+    /// Resolves the dependencies of every registered package version and returns all problems.
+    pub fn check_dependencies(&self) -> Vec<DependencyError> {
+        let mut errors = Vec::new();
+
+        let mut names: Vec<&String> = self.packages.keys().collect();
+        names.sort();
+        for name in names {
+            for version in self.packages[name].keys() {
+                let requirement = VersionReq::parse(&format!("={}", version))
+                    .expect("a version is a valid requirement.");
+                if let Err(e) = self.resolve_dependencies(name, &requirement) {
+                    if !errors.contains(&e) {
+                        errors.push(e);
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
+    fn resolve_rec(
+        &self,
+        package: &Package,
+        required_by: Option<&Package>,
+        requirement: &VersionReq,
+        stack: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        resolved: &mut Vec<ResolvedDependency>,
+    ) -> Result<(), DependencyError> {
+        let id = package_id(package);
+
+        if let Some(pos) = stack.iter().position(|p| *p == id) {
+            let mut chain = stack[pos..].to_vec();
+            chain.push(id);
+            return Err(DependencyError::Cycle { chain });
+        }
+        if done.contains(&id) {
+            return Ok(());
+        }
+
+        stack.push(id.clone());
+
+        let mut dependencies: Vec<(&String, &String)> = package.dependencies.iter().collect();
+        dependencies.sort();
+        for (dep_name, dep_req) in dependencies {
+            let dep_requirement =
+                VersionReq::parse(dep_req).map_err(|e| DependencyError::InvalidRequirement {
+                    package: id.clone(),
+                    dependency: dep_name.clone(),
+                    requirement: dep_req.clone(),
+                    reason: e.to_string(),
+                })?;
+
+            let dependency = self
+                .get_package_matching(dep_name, &dep_requirement)
+                .ok_or_else(|| DependencyError::Missing {
+                    dependency: dep_name.clone(),
+                    requirement: dep_requirement.clone(),
+                    chain: stack.clone(),
+                })?;
+
+            self.resolve_rec(
+                dependency,
+                Some(package),
+                &dep_requirement,
+                stack,
+                done,
+                resolved,
+            )?;
+        }
+
+        stack.pop();
+        done.insert(id);
+        resolved.push(ResolvedDependency {
+            name: package.name.clone(),
+            version: package
+                .semver()
+                .expect("registered packages have a valid version."),
+            required_by: required_by.map(|p| {
+                (
+                    p.name.clone(),
+                    p.semver()
+                        .expect("registered packages have a valid version."),
+                )
+            }),
+            requirement: requirement.clone(),
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn package(name: &str, version: &str, dependencies: &[(&str, &str)]) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            crates: HashMap::new(),
+            dependencies: dependencies
+                .iter()
+                .map(|(n, r)| (n.to_string(), r.to_string()))
+                .collect(),
+            context_objects: HashMap::new(),
+            partials: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn resolve_dependencies_test() {
+        let mut pm = PackageManager::new();
+        pm.add_package(package("app", "1.0.0", &[("math", "^1"), ("io", "^0.2")]))
+            .unwrap();
+        pm.add_package(package("math", "1.1.0", &[("io", "^0.2")]))
+            .unwrap();
+        pm.add_package(package("math", "2.0.0", &[])).unwrap();
+        pm.add_package(package("io", "0.2.3", &[])).unwrap();
+
+        let resolved = pm.resolve_dependencies("app", &VersionReq::STAR).unwrap();
+        let order: Vec<String> = resolved
+            .iter()
+            .map(|r| format!("{}@{}", r.name, r.version))
+            .collect();
+        assert_eq!(vec!["io@0.2.3", "math@1.1.0", "app@1.0.0"], order);
+        assert_eq!(
+            Some(("app".to_string(), Version::new(1, 0, 0))),
+            resolved[1].required_by
+        );
+        assert_eq!(None, resolved[2].required_by);
+        assert!(pm.check_dependencies().is_empty());
+    }
+
+    #[test]
+    fn dependency_errors_test() {
+        let mut pm = PackageManager::new();
+        pm.add_package(package("a", "1.0.0", &[("b", "^1")]))
+            .unwrap();
+        pm.add_package(package("b", "1.0.0", &[("a", "*")]))
+            .unwrap();
+        pm.add_package(package("c", "1.0.0", &[("d", "^3")]))
+            .unwrap();
+
+        assert_eq!(
+            Err(DependencyError::Cycle {
+                chain: vec![
+                    "a@1.0.0".to_string(),
+                    "b@1.0.0".to_string(),
+                    "a@1.0.0".to_string()
+                ]
+            }),
+            pm.resolve_dependencies("a", &VersionReq::STAR)
+        );
+        assert_eq!(
+            Err(DependencyError::Missing {
+                dependency: "d".to_string(),
+                requirement: VersionReq::parse("^3").unwrap(),
+                chain: vec!["c@1.0.0".to_string()]
+            }),
+            pm.resolve_dependencies("c", &VersionReq::STAR)
+        );
+        assert_eq!(3, pm.check_dependencies().len());
+    }
+}
@@ -0,0 +1,754 @@
+use anyhow::Error;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Fields, FnArg, GenericArgument, GenericParam, ImplItem, Item, ItemStruct,
+    PathArguments, ReturnType, TypeParamBound, Visibility, WherePredicate,
+};
+
+use crate::package::{
+    Constructor, Crate, Input, Module, Output, Package, Type, TypeDescription, TypeParameter,
+};
+use crate::templates::pascal_case;
+
+/// How [`extract_crate`] recognizes nodes and their ports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractOptions {
+    /// Attribute marking node structs, e.g. `flow_node` for `#[flow_node]`. Structs with port
+    /// fields are nodes without it.
+    pub node_attribute: String,
+    /// Type of input port fields, e.g. `Input` for `input: Input<T>`. Fields marked with
+    /// `#[input]` are inputs regardless of their type.
+    pub input_type: String,
+    /// Type of output port fields. Fields marked with `#[output]` are outputs.
+    pub output_type: String,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            node_attribute: "flow_node".to_string(),
+            input_type: "Input".to_string(),
+            output_type: "Output".to_string(),
+        }
+    }
+}
+
+/// Something of a node that could not be described, e.g. a constructor with arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractWarning {
+    /// Path of the node or module, e.g. `my_crate::module::Node`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ExtractWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The package crate of a Rust crate's node structs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extraction {
+    pub crate_name: String,
+    pub krate: Crate,
+    pub warnings: Vec<ExtractWarning>,
+}
+
+impl Extraction {
+    /// A package containing only the extracted crate.
+    pub fn into_package(self, name: &str, version: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            crates: HashMap::from([(self.crate_name, self.krate)]),
+            dependencies: HashMap::new(),
+            context_objects: HashMap::new(),
+            partials: HashMap::new(),
+        }
+    }
+}
+
+/// Parses a crate starting at its root file, e.g. `src/lib.rs`, and describes its public node
+/// structs with their type parameters, ports, constructors and traits.
+///
+/// Modules declared as `mod name;` are read from `name.rs` or `name/mod.rs`. Constructors are
+/// inferred from public functions returning `Self` or `Result<Self, _>` in inherent `impl`
+/// blocks of the node's module that take no arguments (`New`), a change observer
+/// (`NewWithObserver`) or a change observer and a context (`NewWithObserverAndContext`), and
+/// from `Default` (`FromDefault`) and `Deserialize` (`FromJson`) implementations.
+pub fn extract_crate(
+    crate_name: &str,
+    root: &Path,
+    options: &ExtractOptions,
+) -> Result<Extraction, Error> {
+    let file = parse_file(root)?;
+    let directory = root.parent().unwrap_or(Path::new("."));
+    extract_items(crate_name, &file.items, Some(directory), options)
+}
+
+/// Like [`extract_crate`] for a crate given as a single source file. Modules declared as
+/// `mod name;` are skipped with a warning.
+pub fn extract_source(
+    crate_name: &str,
+    source: &str,
+    options: &ExtractOptions,
+) -> Result<Extraction, Error> {
+    let file = syn::parse_file(source)
+        .map_err(|e| Error::msg(format!("Could not parse '{}': {}", crate_name, e)))?;
+    extract_items(crate_name, &file.items, None, options)
+}
+
+fn extract_items(
+    crate_name: &str,
+    items: &[Item],
+    directory: Option<&Path>,
+    options: &ExtractOptions,
+) -> Result<Extraction, Error> {
+    let mut extractor = Extractor {
+        options,
+        warnings: Vec::new(),
+    };
+    let module = extractor.module(crate_name, items, directory)?;
+    let mut warnings = extractor.warnings;
+    warnings.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(Extraction {
+        crate_name: crate_name.to_string(),
+        krate: Crate {
+            types: module.types,
+            modules: module.modules,
+            dependency: None,
+        },
+        warnings,
+    })
+}
+
+fn parse_file(path: &Path) -> Result<syn::File, Error> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| Error::msg(format!("Could not read '{}': {}", path.display(), e)))?;
+    syn::parse_file(&source)
+        .map_err(|e| Error::msg(format!("Could not parse '{}': {}", path.display(), e)))
+}
+
+enum Port {
+    Input,
+    Output,
+}
+
+struct Extractor<'a> {
+    options: &'a ExtractOptions,
+    warnings: Vec<ExtractWarning>,
+}
+
+impl Extractor<'_> {
+    fn warn(&mut self, path: &str, message: String) {
+        self.warnings.push(ExtractWarning {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// The nodes of a module. `directory` is where its `mod name;` modules are.
+    fn module(
+        &mut self,
+        path: &str,
+        items: &[Item],
+        directory: Option<&Path>,
+    ) -> Result<Module, Error> {
+        let mut module = Module {
+            types: HashMap::new(),
+            modules: HashMap::new(),
+        };
+        for item in items {
+            match item {
+                Item::Struct(s) if is_public(&s.vis) && self.is_node(s) => {
+                    let type_path = format!("{}::{}", path, s.ident);
+                    let type_desc = self.node(&type_path, s, items);
+                    module.types.insert(s.ident.to_string(), type_desc);
+                }
+                Item::Mod(m) if is_public(&m.vis) && !is_test_only(&m.attrs) => {
+                    let name = m.ident.to_string();
+                    let child_path = format!("{}::{}", path, name);
+                    let child_directory = directory.map(|d| d.join(&name));
+                    let child = match (&m.content, directory) {
+                        (Some((_, child_items)), _) => {
+                            self.module(&child_path, child_items, child_directory.as_deref())?
+                        }
+                        (None, Some(directory)) => {
+                            let file = parse_file(&module_file(directory, &name)?)?;
+                            self.module(&child_path, &file.items, child_directory.as_deref())?
+                        }
+                        (None, None) => {
+                            self.warn(&child_path, "Module is not part of the source.".into());
+                            continue;
+                        }
+                    };
+                    if !child.types.is_empty() || !child.modules.is_empty() {
+                        module.modules.insert(name, child);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(module)
+    }
+
+    fn is_node(&self, s: &ItemStruct) -> bool {
+        has_attribute(&s.attrs, &self.options.node_attribute)
+            || s.fields.iter().any(|field| self.port(field).is_some())
+    }
+
+    fn port(&self, field: &syn::Field) -> Option<Port> {
+        if has_attribute(&field.attrs, "input") {
+            return Some(Port::Input);
+        }
+        if has_attribute(&field.attrs, "output") {
+            return Some(Port::Output);
+        }
+        let syn::Type::Path(type_path) = &field.ty else {
+            return None;
+        };
+        let name = type_path.path.segments.last()?.ident.to_string();
+        if name == self.options.input_type {
+            Some(Port::Input)
+        } else if name == self.options.output_type {
+            Some(Port::Output)
+        } else {
+            None
+        }
+    }
+
+    /// Describes a node struct, `items` are the items of its module.
+    fn node(&mut self, path: &str, s: &ItemStruct, items: &[Item]) -> Type {
+        let type_parameters = self.type_parameters(path, &s.generics);
+
+        let mut inputs = HashMap::new();
+        let mut outputs = HashMap::new();
+        if let Fields::Named(fields) = &s.fields {
+            for field in &fields.named {
+                let (Some(port), Some(ident)) = (self.port(field), &field.ident) else {
+                    continue;
+                };
+                let name = ident.to_string().trim_start_matches("r#").to_string();
+                let Some(port_type) = self.port_type(path, &name, &field.ty, &type_parameters)
+                else {
+                    continue;
+                };
+                match port {
+                    Port::Input => {
+                        inputs.insert(
+                            name,
+                            Input {
+                                input_type: port_type,
+                            },
+                        );
+                    }
+                    Port::Output => {
+                        outputs.insert(
+                            name,
+                            Output {
+                                output_type: port_type,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut constructors = HashMap::new();
+        let mut traits = Vec::new();
+        for derive in derives(&s.attrs) {
+            constructors.extend(trait_constructor(&derive));
+            traits.push(derive);
+        }
+        for item in items {
+            let Item::Impl(imp) = item else {
+                continue;
+            };
+            if !is_type_named(&imp.self_ty, &s.ident) {
+                continue;
+            }
+            match &imp.trait_ {
+                Some((_, trait_path, _)) => {
+                    let trait_name = compact(trait_path.to_token_stream());
+                    constructors.extend(trait_constructor(&trait_name));
+                    traits.push(trait_name);
+                }
+                None => {
+                    for impl_item in &imp.items {
+                        if let ImplItem::Fn(function) = impl_item {
+                            self.add_constructor(path, &s.ident, function, &mut constructors);
+                        }
+                    }
+                }
+            }
+        }
+        traits.sort();
+        traits.dedup();
+
+        Type {
+            inputs: (!inputs.is_empty()).then_some(inputs),
+            outputs: (!outputs.is_empty()).then_some(outputs),
+            type_parameters: (!type_parameters.is_empty()).then_some(type_parameters),
+            constructors,
+            traits,
+        }
+    }
+
+    /// Type parameters with the trait bounds of their declaration and of the `where` clause.
+    fn type_parameters(&mut self, path: &str, generics: &syn::Generics) -> Vec<TypeParameter> {
+        let mut type_parameters = Vec::new();
+        for param in &generics.params {
+            match param {
+                GenericParam::Type(tp) => type_parameters.push(TypeParameter {
+                    name: tp.ident.to_string(),
+                    constraints: trait_bounds(&tp.bounds),
+                }),
+                GenericParam::Lifetime(_) => {}
+                GenericParam::Const(c) => self.warn(
+                    path,
+                    format!("Const parameter '{}' is not supported.", c.ident),
+                ),
+            }
+        }
+
+        for predicate in generics.where_clause.iter().flat_map(|w| &w.predicates) {
+            let WherePredicate::Type(predicate) = predicate else {
+                continue;
+            };
+            let bounded = compact(predicate.bounded_ty.to_token_stream());
+            match type_parameters.iter_mut().find(|tp| tp.name == bounded) {
+                Some(tp) => tp.constraints.extend(trait_bounds(&predicate.bounds)),
+                None => self.warn(
+                    path,
+                    format!(
+                        "Bounds of '{}' are not type parameter constraints.",
+                        bounded
+                    ),
+                ),
+            }
+        }
+        type_parameters
+    }
+
+    /// The type transported by a port, the type argument of e.g. `Input<T>`.
+    fn port_type(
+        &mut self,
+        path: &str,
+        name: &str,
+        field_type: &syn::Type,
+        type_parameters: &[TypeParameter],
+    ) -> Option<TypeDescription> {
+        let argument = match field_type {
+            syn::Type::Path(type_path) => match &type_path.path.segments.last()?.arguments {
+                PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+                    match &arguments.args[0] {
+                        GenericArgument::Type(argument) => Some(argument),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some(argument) = argument else {
+            self.warn(
+                path,
+                format!("Port '{}' has no type argument like `Input<T>`.", name),
+            );
+            return None;
+        };
+
+        let rust_type = compact(argument.to_token_stream());
+        match TypeDescription::parse(&rust_type, type_parameters) {
+            Ok(port_type) => Some(port_type),
+            Err(e) => {
+                self.warn(
+                    path,
+                    format!(
+                        "Type '{}' of port '{}' is not supported: {}",
+                        rust_type, name, e
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    fn add_constructor(
+        &mut self,
+        path: &str,
+        type_name: &syn::Ident,
+        function: &syn::ImplItemFn,
+        constructors: &mut HashMap<String, Constructor>,
+    ) {
+        let signature = &function.sig;
+        if !is_public(&function.vis) || signature.receiver().is_some() {
+            return;
+        }
+        let Some(fallible) = returns_self(&signature.output, type_name) else {
+            return;
+        };
+
+        let parameters: Vec<String> = signature
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(typed) => Some(compact(typed.ty.to_token_stream())),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let function_name = signature.ident.to_string();
+        match function_constructor(&function_name, &parameters, fallible) {
+            Some((name, constructor)) => {
+                constructors.insert(name, constructor);
+            }
+            None => self.warn(
+                path,
+                format!(
+                    "Arguments of constructor '{}' cannot be inferred.",
+                    function_name
+                ),
+            ),
+        }
+    }
+}
+
+/// The constructor of a trait implementation: `Default` for `FromDefault` and `Json` for
+/// `FromJson` of `Deserialize`. Generic arguments like in `Deserialize<'de>` are ignored.
+pub(crate) fn trait_constructor(trait_name: &str) -> Option<(String, Constructor)> {
+    let trait_path = trait_name.split('<').next()?;
+    match trait_path.rsplit("::").next()?.trim() {
+        "Default" => Some(("Default".to_string(), Constructor::FromDefault)),
+        "Deserialize" => Some(("Json".to_string(), Constructor::FromJson)),
+        _ => None,
+    }
+}
+
+/// The constructor of a function returning the node, named like the function in camel case.
+/// `None` if the parameters (Rust types) are not nothing, a change observer or a change observer
+/// and a context.
+pub(crate) fn function_constructor(
+    function_name: &str,
+    parameters: &[String],
+    fallible: bool,
+) -> Option<(String, Constructor)> {
+    let mentions = |rust_type: &String, name: &str| {
+        rust_type
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == name)
+    };
+    let name = pascal_case(function_name);
+    let function_name = (function_name != "new").then(|| function_name.to_string());
+    let constructor = match parameters {
+        [] => Constructor::New {
+            function_name,
+            fallible,
+        },
+        [observer] if mentions(observer, "ChangeObserver") => Constructor::NewWithObserver {
+            function_name,
+            fallible,
+        },
+        [observer, context]
+            if mentions(observer, "ChangeObserver") && mentions(context, "Context") =>
+        {
+            Constructor::NewWithObserverAndContext {
+                function_name,
+                fallible,
+            }
+        }
+        _ => return None,
+    };
+    Some((name, constructor))
+}
+
+/// `mod name;` is read from `name.rs` or `name/mod.rs`.
+fn module_file(directory: &Path, name: &str) -> Result<PathBuf, Error> {
+    let candidates = [
+        directory.join(format!("{}.rs", name)),
+        directory.join(name).join("mod.rs"),
+    ];
+    candidates
+        .iter()
+        .find(|candidate| candidate.is_file())
+        .cloned()
+        .ok_or_else(|| {
+            Error::msg(format!(
+                "Module '{}' is neither at '{}' nor at '{}'.",
+                name,
+                candidates[0].display(),
+                candidates[1].display()
+            ))
+        })
+}
+
+fn is_public(visibility: &Visibility) -> bool {
+    matches!(visibility, Visibility::Public(_))
+}
+
+fn has_attribute(attributes: &[Attribute], name: &str) -> bool {
+    attributes
+        .iter()
+        .any(|a| a.path().segments.last().is_some_and(|s| s.ident == name))
+}
+
+fn is_test_only(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .any(|a| a.path().is_ident("cfg") && compact(a.meta.to_token_stream()) == "cfg(test)")
+}
+
+/// Traits of `#[derive(...)]` attributes, as written.
+fn derives(attributes: &[Attribute]) -> Vec<String> {
+    attributes
+        .iter()
+        .filter(|a| a.path().is_ident("derive"))
+        .filter_map(|a| {
+            a.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .map(|path| compact(path.to_token_stream()))
+        .collect()
+}
+
+/// Trait bounds as constraints, lifetimes and `?Sized` are left out.
+fn trait_bounds(bounds: &Punctuated<TypeParamBound, syn::Token![+]>) -> Vec<String> {
+    bounds
+        .iter()
+        .filter_map(|bound| match bound {
+            TypeParamBound::Trait(tb) if matches!(tb.modifier, syn::TraitBoundModifier::None) => {
+                Some(compact(tb.to_token_stream()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_type_named(rust_type: &syn::Type, name: &syn::Ident) -> bool {
+    match rust_type {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == *name),
+        _ => false,
+    }
+}
+
+/// Whether a function returns the type (`Some(false)`) or a `Result` of it (`Some(true)`).
+fn returns_self(output: &ReturnType, type_name: &syn::Ident) -> Option<bool> {
+    let ReturnType::Type(_, rust_type) = output else {
+        return None;
+    };
+    let is_self = |rust_type: &syn::Type| {
+        is_type_named(rust_type, type_name) || quote!(#rust_type).to_string() == "Self"
+    };
+    if is_self(rust_type) {
+        return Some(false);
+    }
+    let syn::Type::Path(type_path) = rust_type.as_ref() else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if segment.ident == "Result" => {
+            match arguments.args.first() {
+                Some(GenericArgument::Type(ok_type)) if is_self(ok_type) => Some(true),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Renders tokens the way they are written in package JSON, e.g. `Add<Output = T>` instead of
+/// `Add < Output = T >`.
+fn compact(tokens: TokenStream) -> String {
+    let spaced = tokens.to_string();
+    let chars: Vec<char> = spaced.chars().collect();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let mut result = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == ' ' && i > 0 && i + 1 < chars.len() {
+            let (previous, next) = (chars[i - 1], chars[i + 1]);
+            let keep = (is_word(previous) && is_word(next))
+                || matches!(previous, ',' | '=' | '+')
+                || matches!(next, '=' | '+')
+                || next == '-'
+                || (previous == '>' && i > 1 && chars[i - 2] == '-');
+            if !keep {
+                continue;
+            }
+        }
+        result.push(*c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB_RS: &str = r#"
+use flowrs::connection::{Input, Output};
+use flowrs::node::{ChangeObserver, Context};
+
+pub mod math;
+
+#[derive(Clone, Default)]
+pub struct Debug<T> where T: Clone + std::fmt::Debug {
+    #[input]
+    pub input: Input<T>,
+}
+
+impl<T: Clone + std::fmt::Debug> Debug<T> {
+    pub fn new() -> Self {
+        Self { input: Input::new() }
+    }
+}
+
+pub struct Helper {
+    pub value: u32,
+}
+
+#[cfg(test)]
+pub mod tests {
+    pub struct TestNode {
+        pub input: flowrs::connection::Input<u8>,
+    }
+}
+"#;
+
+    const MATH_RS: &str = r#"
+use flowrs::connection::{Input, Output};
+
+#[derive(serde::Deserialize)]
+pub struct Add<I1, I2: Copy, O>
+where
+    I1: std::ops::Add<I2, Output = O> + Send + 'static,
+{
+    pub input_1: Input<I1>,
+    pub input_2: Input<I2>,
+    pub output: Output<Option<O>>,
+}
+
+impl<I1, I2: Copy, O> Add<I1, I2, O> {
+    pub fn new(change_observer: Option<&ChangeObserver>) -> Self {
+        todo!()
+    }
+
+    pub fn with_context(observer: &ChangeObserver, context: Arc<Mutex<Context>>) -> Result<Self, Error> {
+        todo!()
+    }
+
+    pub fn with_offset(offset: u32) -> Self {
+        todo!()
+    }
+
+    fn private() -> Self {
+        todo!()
+    }
+}
+
+#[flow_node]
+pub struct Timer {
+    #[output]
+    pub tick: flowrs::connection::Output<()>,
+    pub interval: Duration,
+}
+
+impl std::fmt::Display for Timer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
+"#;
+
+    #[test]
+    fn extract_test() {
+        let dir =
+            std::env::temp_dir().join(format!("flowrs_package_extract_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("math")).unwrap();
+        fs::write(dir.join("lib.rs"), LIB_RS).unwrap();
+        fs::write(dir.join("math").join("mod.rs"), MATH_RS).unwrap();
+
+        let extraction =
+            extract_crate("nodes", &dir.join("lib.rs"), &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            vec![ExtractWarning {
+                path: "nodes::math::Add".to_string(),
+                message: "Arguments of constructor 'with_offset' cannot be inferred.".to_string()
+            }],
+            extraction.warnings
+        );
+
+        let package = extraction.into_package("nodes", "1.0.0");
+        let paths: Vec<String> = package.types().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            vec!["nodes::Debug", "nodes::math::Add", "nodes::math::Timer"],
+            paths
+        );
+
+        let expected: Package = serde_json::from_str(
+            r#"{
+            "name": "nodes",
+            "version": "1.0.0",
+            "crates": {"nodes": {
+                "types": {
+                    "Debug": {
+                        "inputs": {"input": {"type": "T"}},
+                        "outputs": null,
+                        "type_parameters": [{"name": "T", "where": ["Clone", "std::fmt::Debug"]}],
+                        "constructors": {"New": {"New": {"function_name": null}}, "Default": "FromDefault"},
+                        "traits": ["Clone", "Default"]
+                    }
+                },
+                "modules": {"math": {
+                    "types": {
+                        "Add": {
+                            "inputs": {"input_1": {"type": "I1"}, "input_2": {"type": "I2"}},
+                            "outputs": {"output": {"type": "Option<O>"}},
+                            "type_parameters": [
+                                {"name": "I1", "where": ["std::ops::Add<I2, Output = O>", "Send"]},
+                                {"name": "I2", "where": ["Copy"]},
+                                {"name": "O", "where": []}
+                            ],
+                            "constructors": {
+                                "New": {"NewWithObserver": {"function_name": null}},
+                                "WithContext": {"NewWithObserverAndContext": {"function_name": "with_context", "fallible": true}},
+                                "Json": "FromJson"
+                            },
+                            "traits": ["serde::Deserialize"]
+                        },
+                        "Timer": {
+                            "inputs": null,
+                            "outputs": {"tick": {"type": "()"}},
+                            "type_parameters": null,
+                            "constructors": {},
+                            "traits": ["std::fmt::Display"]
+                        }
+                    },
+                    "modules": {}
+                }}
+            }}
+        }"#,
+        )
+        .expect("wrong format.");
+        assert_eq!(expected, package);
+
+        let extraction = extract_source("nodes", LIB_RS, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            "nodes::math: Module is not part of the source.",
+            extraction.warnings[0].to_string()
+        );
+        assert!(extraction.krate.modules.is_empty());
+    }
+}
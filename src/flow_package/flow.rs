@@ -0,0 +1,458 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::inference::InferredTypeParameters;
+use crate::package::{
+    validate_data, Constructor, DataIssue, ErrorHandling, JsonRead, Namespace, ObjectDescription,
+};
+use crate::package_manager::PackageManager;
+
+/// A flow: named node instances and the connections between their ports.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Flow {
+    pub nodes: HashMap<String, Node>,
+    #[serde(default)]
+    pub connections: Vec<Connection>,
+    /// Objects constructed once and passed to every argument referencing them by name, see
+    /// `ArgumentConstruction::Shared`. They are described like nodes, but their type parameters
+    /// are not inferred.
+    #[serde(default)]
+    pub shared: HashMap<String, Node>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Node {
+    /// Name of the node's type as accepted by `PackageManager::resolve_type`.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Name of the constructor of the node's type that creates the node.
+    pub constructor: String,
+    /// Bindings of the type's type parameters, e.g. `T` -> `f32`. Missing ones can be inferred.
+    #[serde(default)]
+    pub type_parameters: HashMap<String, String>,
+    /// Initialization data read by `FromJson` constructors of the node and its arguments.
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+/// Connects the output port of the `source` node to the input port of the `target` node.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Connection {
+    pub source: String,
+    pub output: String,
+    pub target: String,
+    pub input: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlowTarget {
+    /// `pub fn <name>(data: &serde_json::Value, <parameters>)`. The caller passes the data
+    /// document, see [`Flow::data_document`].
+    Function {
+        name: String,
+        parameters: Vec<String>,
+    },
+    /// `fn main()`, embedding the data document if it is read. The `prelude` lines come first,
+    /// e.g. to create the `change_observer` constructors expect.
+    Main { prelude: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowCodeOptions {
+    pub target: FlowTarget,
+    /// Called as `connect(source.output.clone(), target.input.clone())`.
+    pub connect_function: String,
+    /// If set, called with every node after all connections are made, e.g. `flow.add_node`.
+    pub add_node_function: Option<String>,
+    /// If set, the generated function returns `Result<(), error_type>` and errors are
+    /// propagated instead of panicking, see [`ErrorHandling::Propagate`].
+    pub error_type: Option<String>,
+}
+
+impl Default for FlowCodeOptions {
+    fn default() -> Self {
+        Self {
+            target: FlowTarget::Main {
+                prelude: Vec::new(),
+            },
+            connect_function: "flowrs::connection::connect".to_string(),
+            add_node_function: None,
+            error_type: None,
+        }
+    }
+}
+
+impl Flow {
+    /// The `data` document read by the generated code: the `data` of every node and shared
+    /// object by name.
+    pub fn data_document(&self) -> Value {
+        let mut document = serde_json::Map::new();
+        let mut objects: Vec<(&String, &Node)> = self.nodes.iter().chain(&self.shared).collect();
+        objects.sort_by_key(|(id, _)| *id);
+        for (id, object) in objects {
+            if let Some(data) = &object.data {
+                document.insert(id.clone(), data.clone());
+            }
+        }
+        Value::Object(document)
+    }
+
+    /// Checks the data of all nodes against what the code [`Flow::emit_code`] emits reads.
+    pub fn validate_data(&self, pack_man: &PackageManager) -> Result<Vec<DataIssue>, Error> {
+        let reads = self.json_reads(pack_man)?;
+        Ok(validate_data(&reads, &self.data_document(), pack_man))
+    }
+
+    /// Whether the code [`Flow::emit_code`] emits reads the data document. Unlike
+    /// [`Flow::dependencies`], this does not need the crates of all used types to be registered.
+    pub fn uses_json(&self, pack_man: &PackageManager) -> Result<bool, Error> {
+        let reads = self.json_reads(pack_man)?;
+        Ok(reads.iter().any(|read| !read.at_generation))
+    }
+
+    /// The values the code [`Flow::emit_code`] emits reads from the data document.
+    pub fn json_reads(&self, pack_man: &PackageManager) -> Result<Vec<JsonRead>, Error> {
+        let inferred = self.inferred_type_parameters(pack_man)?;
+
+        let namespace = Namespace::with_context_objects(pack_man);
+        let mut reads = Vec::new();
+        for id in &self.construction_order(pack_man)? {
+            let (constructor, obj_desc) = self.node_constructor(pack_man, id, &inferred[id])?;
+            reads.extend(constructor.json_reads(&obj_desc, &inferred[id], pack_man, &namespace)?);
+        }
+
+        Ok(reads)
+    }
+
+    /// Emits the Rust code that constructs all shared objects and nodes and connects the ports
+    /// of the nodes.
+    ///
+    /// Type parameters that are not bound by the nodes are inferred, see
+    /// [`PackageManager::infer_type_parameters`].
+    pub fn emit_code(
+        &self,
+        pack_man: &PackageManager,
+        options: &FlowCodeOptions,
+    ) -> Result<String, Error> {
+        let inferred = self.inferred_type_parameters(pack_man)?;
+        let error_handling = if options.error_type.is_some() {
+            ErrorHandling::Propagate
+        } else {
+            ErrorHandling::Panic
+        };
+
+        let mut body = Vec::<String>::new();
+
+        // All objects share one scope, so their variables cannot collide.
+        let namespace = Namespace::with_context_objects(pack_man).with_data(self.data_document());
+        for id in &self.construction_order(pack_man)? {
+            let code = self.emit_node_construction(
+                pack_man,
+                id,
+                &inferred[id],
+                &namespace,
+                error_handling,
+            )?;
+            body.extend(
+                code.lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(String::from),
+            );
+        }
+
+        if !self.connections.is_empty() {
+            body.push("".to_string());
+        }
+        for c in &self.connections {
+            body.push(format!(
+                "{}({}.{}.clone(), {}.{}.clone());",
+                options.connect_function,
+                namespace.qualified_name(&c.source),
+                c.output,
+                namespace.qualified_name(&c.target),
+                c.input
+            ));
+        }
+
+        if let Some(add_node) = &options.add_node_function {
+            body.push("".to_string());
+            let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+            node_ids.sort();
+            for id in node_ids {
+                body.push(format!("{}({});", add_node, namespace.qualified_name(id)));
+            }
+        }
+
+        if options.error_type.is_some() {
+            body.push("".to_string());
+            body.push("Ok(())".to_string());
+        }
+
+        let return_type = match &options.error_type {
+            Some(error_type) => format!(" -> Result<(), {}>", error_type),
+            None => "".to_string(),
+        };
+        let (signature, mut prelude) = match &options.target {
+            FlowTarget::Function { name, parameters } => {
+                let mut params = vec!["data: &serde_json::Value".to_string()];
+                params.extend(parameters.iter().cloned());
+                (
+                    format!(
+                        "#[allow(unused_variables)]\npub fn {}({}){}",
+                        name,
+                        params.join(", "),
+                        return_type
+                    ),
+                    Vec::new(),
+                )
+            }
+            FlowTarget::Main { prelude } => {
+                let mut lines = prelude.clone();
+                if self.uses_json(pack_man)? {
+                    lines.push(self.emit_embedded_data(error_handling));
+                }
+                (format!("fn main(){}", return_type), lines)
+            }
+        };
+        if !prelude.is_empty() {
+            prelude.push("".to_string());
+        }
+
+        let indented: Vec<String> = prelude
+            .iter()
+            .chain(body.iter())
+            .map(|line| {
+                if line.is_empty() {
+                    line.clone()
+                } else {
+                    format!("    {}", line)
+                }
+            })
+            .collect();
+
+        Ok(format!(
+            "// Generated by flowrs-package.\n\n{} {{\n{}\n}}\n",
+            signature,
+            indented.join("\n")
+        ))
+    }
+
+    /// Infers the type parameters of all nodes, see [`PackageManager::infer_type_parameters`].
+    /// Shared objects keep the bindings they are given.
+    pub(crate) fn inferred_type_parameters(
+        &self,
+        pack_man: &PackageManager,
+    ) -> Result<InferredTypeParameters, Error> {
+        let mut inferred = pack_man.infer_type_parameters(self).map_err(|errors| {
+            Error::msg(
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            )
+        })?;
+        for (id, object) in &self.shared {
+            inferred.insert(id.clone(), object.type_parameters.clone());
+        }
+        Ok(inferred)
+    }
+
+    fn emit_node_construction(
+        &self,
+        pack_man: &PackageManager,
+        id: &str,
+        type_parameters: &HashMap<String, String>,
+        namespace: &Namespace,
+        error_handling: ErrorHandling,
+    ) -> Result<String, Error> {
+        let (constructor, obj_desc) = self.node_constructor(pack_man, id, type_parameters)?;
+        constructor.emit_code_template_with(
+            &obj_desc,
+            type_parameters,
+            pack_man,
+            namespace,
+            error_handling,
+        )
+    }
+
+    /// The constructor creating a node or shared object and the description of the object.
+    pub(crate) fn node_constructor<'a>(
+        &self,
+        pack_man: &'a PackageManager,
+        id: &str,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<(&'a Constructor, ObjectDescription), Error> {
+        let node = self
+            .nodes
+            .get(id)
+            .or_else(|| self.shared.get(id))
+            .ok_or_else(|| Error::msg(format!("Node '{}' not found.", id)))?;
+        let type_desc = pack_man.resolve_type(&node.type_name)?.type_desc;
+        let constructor = type_desc
+            .constructors
+            .get(&node.constructor)
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "Constructor '{}' for type '{}' of node '{}' not found.",
+                    node.constructor, node.type_name, id
+                ))
+            })?;
+
+        let obj_desc = type_desc.object_description(&node.type_name, id, type_parameters)?;
+
+        Ok((constructor, obj_desc))
+    }
+
+    fn emit_embedded_data(&self, error_handling: ErrorHandling) -> String {
+        let json = self.data_document().to_string();
+        // Use enough hashes for the raw string to never end inside the document.
+        let mut hashes = "#".to_string();
+        while json.contains(&format!("\"{}", hashes)) {
+            hashes.push('#');
+        }
+        format!(
+            "let data: serde_json::Value = serde_json::from_str(r{h}\"{}\"{h}){};",
+            json,
+            match error_handling {
+                ErrorHandling::Panic => ".expect(\"Could not parse flow data.\")",
+                ErrorHandling::Propagate => "?",
+            },
+            h = hashes
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+    "nodes": {
+        "types": {
+        "Source": {
+            "inputs": null,
+            "outputs": {"output": {"type": "T"}},
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {"New": {"NewWithObserver": {"function_name": null}}}
+        },
+        "Scale": {
+            "inputs": {"input": {"type": "f32"}},
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"New": {"NewWithArbitraryArgs": {
+                "function_name": "with_factor",
+                "fallible": true,
+                "arguments": [
+                    {"type": "f32", "name": "factor", "passing": "Move", "construction": {"Constructor": "Json"}}
+                ]
+            }}}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    const FLOW_JSON: &str = r#"
+{
+    "nodes": {
+        "source": {"type": "nodes::Source", "constructor": "New"},
+        "scale": {"type": "nodes::Scale", "constructor": "New", "data": {"factor": 2.5}}
+    },
+    "connections": [
+        {"source": "source", "output": "output", "target": "scale", "input": "input"}
+    ]
+}
+    "#;
+
+    #[test]
+    fn emit_flow_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        let flow: Flow = serde_json::from_str(FLOW_JSON).expect("wrong format.");
+
+        let options = FlowCodeOptions {
+            target: FlowTarget::Main {
+                prelude: vec!["let change_observer = ChangeObserver::new();".to_string()],
+            },
+            add_node_function: Some("flow.add_node".to_string()),
+            ..Default::default()
+        };
+        let code = flow.emit_code(&pm, &options).expect("emission failed.");
+        assert_eq!(
+            r##"// Generated by flowrs-package.
+
+fn main() {
+    let change_observer = ChangeObserver::new();
+    let data: serde_json::Value = serde_json::from_str(r#"{"scale":{"factor":2.5}}"#).expect("Could not parse flow data.");
+
+    let scale_factor: f32 = serde_json::from_value(data["scale"]["factor"].clone()).expect("Could not create 'scale_factor' from Json.");
+    let scale = nodes::Scale::with_factor(scale_factor).expect("Could not create 'scale'.");
+    let source = nodes::Source::<f32>::new(change_observer.clone());
+
+    flowrs::connection::connect(source.output.clone(), scale.input.clone());
+
+    flow.add_node(scale);
+    flow.add_node(source);
+}
+"##,
+            code
+        );
+
+        let options = FlowCodeOptions {
+            target: FlowTarget::Function {
+                name: "build".to_string(),
+                parameters: vec!["change_observer: &ChangeObserver".to_string()],
+            },
+            ..Default::default()
+        };
+        let code = flow.emit_code(&pm, &options).expect("emission failed.");
+        assert!(code.contains(
+            "pub fn build(data: &serde_json::Value, change_observer: &ChangeObserver) {"
+        ));
+        assert!(!code.contains("let data"));
+
+        let options = FlowCodeOptions {
+            error_type: Some("Box<dyn std::error::Error>".to_string()),
+            ..Default::default()
+        };
+        let code = flow.emit_code(&pm, &options).expect("emission failed.");
+        assert!(code.contains("fn main() -> Result<(), Box<dyn std::error::Error>> {"));
+        assert!(code.contains(r##"serde_json::from_str(r#"{"scale":{"factor":2.5}}"#)?;"##));
+        assert!(code.contains(r#"serde_json::from_value(data["scale"]["factor"].clone())?;"#));
+        assert!(code.contains("nodes::Scale::with_factor(scale_factor)?;"));
+        assert!(code.ends_with("    Ok(())\n}\n"));
+        assert!(!code.contains("expect"));
+
+        // Bindings to types of crates no package describes are emitted as they are.
+        let flow: Flow = serde_json::from_str(
+            r#"{"nodes": {"source": {"type": "nodes::Source", "constructor": "New", "type_parameters": {"T": "chrono::Utc"}}}}"#,
+        )
+        .expect("wrong format.");
+        let code = flow
+            .emit_code(&pm, &FlowCodeOptions::default())
+            .expect("emission failed.");
+        assert!(code
+            .contains("let source = nodes::Source::<chrono::Utc>::new(change_observer.clone());"));
+        assert!(!code.contains("let data"));
+        let dependencies = flow.dependencies(&pm).unwrap();
+        assert!(dependencies.undescribed_crates.contains("chrono"));
+
+        assert_eq!(
+            "Node 'nope' not found.",
+            flow.node_constructor(&pm, "nope", &HashMap::new())
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
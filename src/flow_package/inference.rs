@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::flow::Flow;
+use crate::package::{Type, TypeDescription};
+use crate::package_manager::PackageManager;
+use crate::type_check::{
+    contains_generics, scope_generics, scoped_name, ConstraintViolation, Unifier,
+};
+
+/// Type parameter bindings per node, e.g. `add` -> (`T` -> `f32`).
+pub type InferredTypeParameters = HashMap<String, HashMap<String, String>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InferenceError {
+    UnknownNode {
+        node: String,
+    },
+    UnknownType {
+        node: String,
+        reason: String,
+    },
+    UnknownPort {
+        node: String,
+        port: String,
+    },
+    InvalidBinding {
+        node: String,
+        parameter: String,
+        reason: String,
+    },
+    /// The types of a connection's ports cannot be unified.
+    Conflict {
+        source: String,
+        output: String,
+        target: String,
+        input: String,
+        reason: String,
+    },
+    /// Nothing determines the parameter. `ports` lists the node's ports that use it.
+    Unbound {
+        node: String,
+        parameter: String,
+        ports: Vec<String>,
+    },
+    /// A (possibly inferred) binding violates a `where` constraint.
+    Unsatisfied {
+        node: String,
+        violation: ConstraintViolation,
+    },
+}
+
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InferenceError::UnknownNode { node } => write!(f, "Node '{}' not found.", node),
+            InferenceError::UnknownType { node, reason } => {
+                write!(f, "Type of node '{}' not found: {}", node, reason)
+            }
+            InferenceError::UnknownPort { node, port } => {
+                write!(f, "Port '{}' of node '{}' not found.", port, node)
+            }
+            InferenceError::InvalidBinding {
+                node,
+                parameter,
+                reason,
+            } => write!(
+                f,
+                "Invalid binding of '{}' at node '{}': {}",
+                parameter, node, reason
+            ),
+            InferenceError::Conflict {
+                source,
+                output,
+                target,
+                input,
+                reason,
+            } => write!(
+                f,
+                "Connection '{}.{}' -> '{}.{}' has conflicting types: {}",
+                source, output, target, input, reason
+            ),
+            InferenceError::Unbound {
+                node,
+                parameter,
+                ports,
+            } => write!(
+                f,
+                "Type parameter '{}' of node '{}' cannot be inferred (used by ports: {}).",
+                parameter,
+                node,
+                ports.join(", ")
+            ),
+            InferenceError::Unsatisfied { node, violation } => {
+                write!(f, "Node '{}': {}", node, violation)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InferenceError {}
+
+impl PackageManager {
+    /// Infers the type parameters of all nodes of a flow from the types of connected ports.
+    ///
+    /// Explicit bindings of the nodes are kept. All problems are collected: connections whose
+    /// types conflict, type parameters that are still unbound after inference and bindings that
+    /// violate `where` constraints.
+    pub fn infer_type_parameters(
+        &self,
+        flow: &Flow,
+    ) -> Result<InferredTypeParameters, Vec<InferenceError>> {
+        let mut errors = Vec::new();
+        let mut unifier = Unifier::new();
+
+        let mut node_ids: Vec<&String> = flow.nodes.keys().collect();
+        node_ids.sort();
+
+        let mut types: HashMap<&str, &Type> = HashMap::new();
+        for id in &node_ids {
+            let node = &flow.nodes[*id];
+            match self.resolve_type(&node.type_name) {
+                Ok(resolved) => {
+                    types.insert(id.as_str(), resolved.type_desc);
+                }
+                Err(e) => {
+                    errors.push(InferenceError::UnknownType {
+                        node: id.to_string(),
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            }
+
+            let mut bindings: Vec<(&String, &String)> = node.type_parameters.iter().collect();
+            bindings.sort();
+            for (parameter, type_name) in bindings {
+                let result = TypeDescription::from_str(type_name)
+                    .map_err(|e| e.to_string())
+                    .and_then(|td| unifier.bind(&scoped_name(id, parameter), td));
+                if let Err(reason) = result {
+                    errors.push(InferenceError::InvalidBinding {
+                        node: id.to_string(),
+                        parameter: parameter.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        for connection in &flow.connections {
+            let output = port_type(&types, flow, &connection.source, &connection.output, false);
+            let input = port_type(&types, flow, &connection.target, &connection.input, true);
+            let (output, input) = match (output, input) {
+                (Ok(Some(output)), Ok(Some(input))) => (output, input),
+                (output, input) => {
+                    errors.extend(output.err());
+                    errors.extend(input.err());
+                    continue;
+                }
+            };
+
+            // A failed unification may have bound some variables already, so it is undone.
+            let mut attempt = unifier.clone();
+            match attempt.unify(&output, &input) {
+                Ok(()) => unifier = attempt,
+                Err(reason) => errors.push(InferenceError::Conflict {
+                    source: connection.source.clone(),
+                    output: connection.output.clone(),
+                    target: connection.target.clone(),
+                    input: connection.input.clone(),
+                    reason,
+                }),
+            }
+        }
+
+        let mut inferred = InferredTypeParameters::new();
+        for id in node_ids {
+            let Some(type_desc) = types.get(id.as_str()) else {
+                continue;
+            };
+            let mut bindings = HashMap::new();
+            for tp in type_desc.type_parameters.iter().flatten() {
+                match unifier.binding(&scoped_name(id, &tp.name)) {
+                    Some(td) if !contains_generics(&td) => {
+                        bindings.insert(tp.name.clone(), td.to_string());
+                    }
+                    _ => errors.push(InferenceError::Unbound {
+                        node: id.clone(),
+                        parameter: tp.name.clone(),
+                        ports: ports_using(type_desc, &tp.name),
+                    }),
+                }
+            }
+            if let Err(violations) = self.check_type_parameter_constraints(type_desc, &bindings) {
+                errors.extend(violations.into_iter().map(|violation| {
+                    InferenceError::Unsatisfied {
+                        node: id.clone(),
+                        violation,
+                    }
+                }));
+            }
+            inferred.insert(id.clone(), bindings);
+        }
+
+        if errors.is_empty() {
+            Ok(inferred)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// The type of a node's port with the node's generics scoped by the node id.
+///
+/// `None` if the node's type is unknown, which was reported already.
+fn port_type(
+    types: &HashMap<&str, &Type>,
+    flow: &Flow,
+    node: &str,
+    port: &str,
+    is_input: bool,
+) -> Result<Option<TypeDescription>, InferenceError> {
+    if !flow.nodes.contains_key(node) {
+        return Err(InferenceError::UnknownNode {
+            node: node.to_string(),
+        });
+    }
+    let Some(type_desc) = types.get(node) else {
+        return Ok(None);
+    };
+
+    let td = if is_input {
+        type_desc
+            .inputs
+            .as_ref()
+            .and_then(|i| i.get(port))
+            .map(|i| &i.input_type)
+    } else {
+        type_desc
+            .outputs
+            .as_ref()
+            .and_then(|o| o.get(port))
+            .map(|o| &o.output_type)
+    };
+
+    td.map(|td| Some(scope_generics(td, node)))
+        .ok_or_else(|| InferenceError::UnknownPort {
+            node: node.to_string(),
+            port: port.to_string(),
+        })
+}
+
+fn ports_using(type_desc: &Type, parameter: &str) -> Vec<String> {
+    fn uses(td: &TypeDescription, parameter: &str) -> bool {
+        matches!(td, TypeDescription::Generic { name, .. } if name == parameter)
+            || td.children().into_iter().any(|c| uses(c, parameter))
+    }
+
+    let inputs = type_desc
+        .inputs
+        .iter()
+        .flatten()
+        .map(|(name, i)| (name, &i.input_type));
+    let outputs = type_desc
+        .outputs
+        .iter()
+        .flatten()
+        .map(|(name, o)| (name, &o.output_type));
+
+    let mut ports: Vec<String> = inputs
+        .chain(outputs)
+        .filter(|(_, td)| uses(td, parameter))
+        .map(|(name, _)| name.clone())
+        .collect();
+    ports.sort();
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::{Connection, Node};
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "math",
+    "version": "1.0.0",
+    "crates": {
+    "math": {
+        "types": {
+        "Constant": {
+            "inputs": null,
+            "outputs": {"out": {"type": "T"}},
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {}
+        },
+        "Add": {
+            "inputs": {"a": {"type": "T"}, "b": {"type": "T"}},
+            "outputs": {"sum": {"type": "T"}},
+            "type_parameters": [{"name": "T", "where": ["Add"]}],
+            "constructors": {}
+        },
+        "Print": {
+            "inputs": {"in": {"type": "String"}},
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    fn node(type_name: &str, bindings: &[(&str, &str)]) -> Node {
+        Node {
+            type_name: type_name.to_string(),
+            constructor: "New".to_string(),
+            type_parameters: bindings
+                .iter()
+                .map(|(p, t)| (p.to_string(), t.to_string()))
+                .collect(),
+            data: None,
+        }
+    }
+
+    fn connection(source: &str, output: &str, target: &str, input: &str) -> Connection {
+        Connection {
+            source: source.to_string(),
+            output: output.to_string(),
+            target: target.to_string(),
+            input: input.to_string(),
+        }
+    }
+
+    fn flow(with_print: bool) -> Flow {
+        let mut flow = Flow::default();
+        flow.nodes
+            .insert("one".into(), node("math::Constant", &[("T", "f32")]));
+        flow.nodes.insert("two".into(), node("math::Constant", &[]));
+        flow.nodes.insert("add".into(), node("math::Add", &[]));
+        flow.connections.push(connection("one", "out", "add", "a"));
+        flow.connections.push(connection("two", "out", "add", "b"));
+        if with_print {
+            flow.nodes.insert("print".into(), node("math::Print", &[]));
+            flow.connections
+                .push(connection("add", "sum", "print", "in"));
+        }
+        flow
+    }
+
+    fn package_manager() -> PackageManager {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        pm
+    }
+
+    #[test]
+    fn infer_type_parameters_test() {
+        let pm = package_manager();
+        let inferred = pm.infer_type_parameters(&flow(false)).unwrap();
+        for node in ["one", "two", "add"] {
+            assert_eq!("f32", inferred[node]["T"]);
+        }
+    }
+
+    #[test]
+    fn inference_errors_test() {
+        let pm = package_manager();
+        let errors = pm.infer_type_parameters(&flow(true)).unwrap_err();
+        assert_eq!(1, errors.len());
+        assert!(
+            matches!(&errors[0], InferenceError::Conflict { source, target, .. } if source == "add" && target == "print"),
+            "{:?}",
+            errors
+        );
+
+        let mut flags = flow(false);
+        flags
+            .nodes
+            .get_mut("one")
+            .unwrap()
+            .type_parameters
+            .insert("T".to_string(), "bool".to_string());
+        let errors = pm.infer_type_parameters(&flags).unwrap_err();
+        assert!(
+            matches!(&errors[..], [InferenceError::Unsatisfied { node, violation }] if node == "add" && violation.trait_name == "Add"),
+            "{:?}",
+            errors
+        );
+
+        let mut lonely = Flow::default();
+        lonely.nodes.insert("add".into(), node("math::Add", &[]));
+        assert_eq!(
+            vec![InferenceError::Unbound {
+                node: "add".to_string(),
+                parameter: "T".to_string(),
+                ports: vec!["a".to_string(), "b".to_string(), "sum".to_string()]
+            }],
+            pm.infer_type_parameters(&lonely).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::Error;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+use crate::flow::{Flow, FlowCodeOptions, FlowTarget};
+use crate::package::{
+    ArgumentConstruction, Constructor, CrateDependency, CrateSource, TypeDescription,
+};
+use crate::package_manager::{split_qualified_type_name, PackageManager, TypeLookupError};
+
+/// Crate roots that are always available and therefore never become dependencies.
+const STANDARD_CRATES: [&str; 3] = ["std", "core", "alloc"];
+
+/// What the code generated for a flow needs to compile.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FlowDependencies {
+    /// Dependencies by crate name.
+    pub crates: BTreeMap<String, CrateDependency>,
+    /// Used crates whose packages do not describe where to get them from.
+    pub undescribed_crates: BTreeSet<String>,
+    /// Whether the generated code reads the data document, which requires `serde_json`.
+    pub uses_json: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestOptions {
+    pub name: String,
+    pub version: String,
+    pub edition: String,
+    pub serde_json_version: String,
+    /// Dependencies not described by any package, e.g. the crate of
+    /// `FlowCodeOptions::connect_function`. They take precedence over package crates.
+    pub extra_dependencies: BTreeMap<String, CrateDependency>,
+}
+
+impl Default for ManifestOptions {
+    fn default() -> Self {
+        Self {
+            name: "flow".to_string(),
+            version: "0.1.0".to_string(),
+            edition: "2021".to_string(),
+            serde_json_version: "1.0".to_string(),
+            extra_dependencies: BTreeMap::new(),
+        }
+    }
+}
+
+impl Flow {
+    /// Collects the crates of all types the generated code of the flow uses: node and shared
+    /// object types, their type parameter bindings and all arguments constructed on the way.
+    pub fn dependencies(&self, pack_man: &PackageManager) -> Result<FlowDependencies, Error> {
+        let inferred = self.inferred_type_parameters(pack_man)?;
+
+        let mut collector = DependencyCollector {
+            pack_man,
+            dependencies: FlowDependencies::default(),
+        };
+
+        for id in &self.construction_order(pack_man)? {
+            let type_parameters = &inferred[id];
+
+            let object = self.nodes.get(id).unwrap_or_else(|| &self.shared[id]);
+            collector.add_type_name(&object.type_name)?;
+            for binding in type_parameters.values() {
+                collector
+                    .add_type_description(&TypeDescription::from_str(binding)?, type_parameters)?;
+            }
+
+            let (constructor, _) = self.node_constructor(pack_man, id, type_parameters)?;
+            collector.add_constructor(constructor, type_parameters)?;
+        }
+        collector.dependencies.uses_json = self.uses_json(pack_man)?;
+
+        Ok(collector.dependencies)
+    }
+
+    /// Emits the `Cargo.toml` of a project containing the code generated by
+    /// [`Flow::emit_code`] with the same options.
+    pub fn emit_cargo_toml(
+        &self,
+        pack_man: &PackageManager,
+        code_options: &FlowCodeOptions,
+        options: &ManifestOptions,
+    ) -> Result<String, Error> {
+        let dependencies = self.dependencies(pack_man)?;
+
+        let mut crates = dependencies.crates;
+        crates.extend(options.extra_dependencies.clone());
+        let undescribed: Vec<&String> = dependencies
+            .undescribed_crates
+            .iter()
+            .filter(|c| !crates.contains_key(*c))
+            .collect();
+        if !undescribed.is_empty() {
+            return Err(Error::msg(format!(
+                "No dependency description for crates: {}",
+                undescribed
+                    .iter()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )));
+        }
+        // Library functions take the data document as `serde_json::Value`.
+        if dependencies.uses_json || matches!(code_options.target, FlowTarget::Function { .. }) {
+            crates
+                .entry("serde_json".to_string())
+                .or_insert_with(|| CrateDependency {
+                    source: CrateSource::CratesIo {
+                        version: options.serde_json_version.clone(),
+                    },
+                    features: Vec::new(),
+                });
+        }
+
+        let mut lines = vec![
+            "[package]".to_string(),
+            format!("name = {}", toml_string(&options.name)),
+            format!("version = {}", toml_string(&options.version)),
+            format!("edition = {}", toml_string(&options.edition)),
+            "".to_string(),
+            "[dependencies]".to_string(),
+        ];
+        for (name, dependency) in &crates {
+            lines.push(format!("{} = {}", name, emit_dependency(dependency)));
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+struct DependencyCollector<'a> {
+    pack_man: &'a PackageManager,
+    dependencies: FlowDependencies,
+}
+
+impl DependencyCollector<'_> {
+    fn add_type_name(&mut self, type_name: &str) -> Result<(), Error> {
+        let (_, path) = split_qualified_type_name(type_name);
+        // Built-in types have no crate.
+        let Some((crate_name, _)) = path.split_once("::") else {
+            return Ok(());
+        };
+        if STANDARD_CRATES.contains(&crate_name) {
+            return Ok(());
+        }
+
+        // Types of crates no package describes, e.g. type parameter bindings like
+        // `chrono::Utc`, need a dependency from `ManifestOptions::extra_dependencies`.
+        let package = match self.pack_man.resolve_type(type_name) {
+            Ok(resolved) => Some(resolved.package),
+            Err(TypeLookupError::NotFound(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+        match package
+            .and_then(|p| p.crates.get(crate_name))
+            .and_then(|c| c.dependency.as_ref())
+        {
+            Some(dependency) => {
+                self.dependencies
+                    .crates
+                    .insert(crate_name.to_string(), dependency.clone());
+            }
+            None => {
+                self.dependencies
+                    .undescribed_crates
+                    .insert(crate_name.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn add_type_description(
+        &mut self,
+        td: &TypeDescription,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        match td {
+            TypeDescription::Type { name, .. } => self.add_type_name(name)?,
+            TypeDescription::Generic { name, .. } => {
+                if let Some(binding) = type_parameters.get(name) {
+                    self.add_type_description(
+                        &TypeDescription::from_str(binding)?,
+                        type_parameters,
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        for child in td.children() {
+            self.add_type_description(child, type_parameters)?;
+        }
+        Ok(())
+    }
+
+    fn add_constructor(
+        &mut self,
+        constructor: &Constructor,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        match constructor {
+            Constructor::NewWithArbitraryArgs { .. } | Constructor::FromCode { .. } => {
+                for arg in constructor.arguments(self.pack_man, type_parameters)? {
+                    let ArgumentConstruction::Constructor(constructor_name) = &arg.construction
+                    else {
+                        continue;
+                    };
+                    self.add_type_description(&arg.arg_type, type_parameters)?;
+
+                    let (arg_constructor, _, arg_type_parameters) =
+                        arg.constructor(constructor_name, self.pack_man, type_parameters)?;
+                    self.add_constructor(arg_constructor, &arg_type_parameters)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn emit_dependency(dependency: &CrateDependency) -> String {
+    let mut entries = match &dependency.source {
+        CrateSource::CratesIo { version } => vec![format!("version = {}", toml_string(version))],
+        CrateSource::Path { path } => vec![format!("path = {}", toml_string(path))],
+        CrateSource::Git { git, branch, rev } => {
+            let mut entries = vec![format!("git = {}", toml_string(git))];
+            if let Some(branch) = branch {
+                entries.push(format!("branch = {}", toml_string(branch)));
+            }
+            if let Some(rev) = rev {
+                entries.push(format!("rev = {}", toml_string(rev)));
+            }
+            entries
+        }
+    };
+    if !dependency.features.is_empty() {
+        entries.push(format!(
+            "features = [{}]",
+            dependency
+                .features
+                .iter()
+                .map(|f| toml_string(f))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+    format!("{{ {} }}", entries.join(", "))
+}
+
+/// Json string literals are valid TOML basic strings.
+fn toml_string(s: &str) -> String {
+    serde_json::Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+    "nodes": {
+        "dependency": {"git": "https://example.com/nodes.git", "branch": "main", "features": ["serde"]},
+        "types": {
+        "Scale": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"New": {"NewWithArbitraryArgs": {
+                "function_name": "with_params",
+                "arguments": [
+                    {"type": "params::Params", "name": "params", "passing": "Move", "construction": {"Constructor": "Json"}}
+                ]
+            }}}
+        }
+        },
+        "modules": {}
+    },
+    "params": {
+        "dependency": {"path": "../params"},
+        "types": {
+        "Params": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"Json": "FromJson"}
+        }
+        },
+        "modules": {}
+    },
+    "unused": {
+        "dependency": {"version": "0.3"},
+        "types": {},
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    #[test]
+    fn emit_cargo_toml_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        let flow: Flow = serde_json::from_str(
+            r#"{"nodes": {"scale": {"type": "nodes::Scale", "constructor": "New", "data": {"params": {}}}}}"#,
+        )
+        .expect("wrong format.");
+
+        let mut options = ManifestOptions::default();
+        options.extra_dependencies.insert(
+            "flowrs".to_string(),
+            CrateDependency {
+                source: CrateSource::CratesIo {
+                    version: "0.1".to_string(),
+                },
+                features: Vec::new(),
+            },
+        );
+        let manifest = flow
+            .emit_cargo_toml(&pm, &FlowCodeOptions::default(), &options)
+            .expect("emission failed.");
+        assert_eq!(
+            r#"[package]
+name = "flow"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+flowrs = { version = "0.1" }
+nodes = { git = "https://example.com/nodes.git", branch = "main", features = ["serde"] }
+params = { path = "../params" }
+serde_json = { version = "1.0" }
+"#,
+            manifest
+        );
+
+        // Without Json nothing needs serde_json.
+        let flow: Flow =
+            serde_json::from_str(r#"{"nodes": {"x": {"type": "i32", "constructor": "Default"}}}"#)
+                .expect("wrong format.");
+        let dependencies = flow.dependencies(&pm).unwrap();
+        assert!(!dependencies.uses_json);
+        assert!(dependencies.crates.is_empty());
+
+        // Templates read the document with `json_path`.
+        let mut pm = PackageManager::new();
+        let mut package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        let scale = package
+            .crates
+            .get_mut("nodes")
+            .unwrap()
+            .types
+            .get_mut("Scale")
+            .unwrap();
+        scale.constructors.insert(
+            "Code".to_string(),
+            Constructor::FromCode {
+                code_template:
+                    "let {{fully_qualified_name}} = Scale::from({{json_path \"factor\"}}.as_f64());"
+                        .to_string(),
+                arguments: Vec::new(),
+                package: None,
+            },
+        );
+        pm.add_package(package).unwrap();
+        let flow: Flow = serde_json::from_str(
+            r#"{"nodes": {"scale": {"type": "nodes::Scale", "constructor": "Code", "data": {"factor": 2.5}}}}"#,
+        )
+        .expect("wrong format.");
+        assert!(flow.dependencies(&pm).unwrap().uses_json);
+        assert!(flow
+            .emit_cargo_toml(
+                &pm,
+                &FlowCodeOptions::default(),
+                &ManifestOptions::default()
+            )
+            .unwrap()
+            .contains("serde_json = { version = \"1.0\" }"));
+
+        let mut pm = PackageManager::new();
+        let mut package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        package.crates.get_mut("params").unwrap().dependency = None;
+        pm.add_package(package).unwrap();
+        let flow: Flow = serde_json::from_str(
+            r#"{"nodes": {"scale": {"type": "nodes::Scale", "constructor": "New"}}}"#,
+        )
+        .expect("wrong format.");
+        assert!(flow
+            .emit_cargo_toml(
+                &pm,
+                &FlowCodeOptions::default(),
+                &ManifestOptions::default()
+            )
+            .is_err());
+    }
+}
@@ -1,10 +1,15 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::package_manager::PackageManager;
+use crate::package_manager::{split_qualified_type_name, PackageManager};
+use crate::templates::{template_uses, TemplateUses};
+use crate::validate::{scope_types, scopes};
 use anyhow::{Error, Result};
-use handlebars::Handlebars;
+use semver::Version;
+use serde_json::Value;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
 use std::str::FromStr;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
@@ -12,12 +17,80 @@ pub struct Package {
     pub name: String,
     pub version: String,
     pub crates: HashMap<String, Crate>,
+    /// Packages this package uses types from, by name with a semver requirement (e.g. `^1.2`).
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    /// Objects this package expects to exist where flows are constructed, by name, see
+    /// [`ContextObject`].
+    #[serde(default)]
+    pub context_objects: HashMap<String, ContextObject>,
+    /// Handlebars partials the `FromCode` templates of this package can use, by name.
+    #[serde(default)]
+    pub partials: HashMap<String, String>,
+}
+
+impl Package {
+    /// Parses `version` as semantic version.
+    pub fn semver(&self) -> Result<Version, semver::Error> {
+        Version::parse(&self.version)
+    }
+
+    /// All types of all crates and modules with their path, e.g. `my_crate::module::Type`.
+    /// Crates, modules and types are sorted by name.
+    pub fn types(&self) -> Vec<(String, &Type)> {
+        scopes(self)
+            .iter()
+            .flat_map(scope_types)
+            .map(|(_, path, type_desc)| (path, type_desc))
+            .collect()
+    }
+
+    /// JSON pointer to the type with the given path in the package document, e.g.
+    /// `/crates/my_crate/modules/module/types/Type` for `my_crate::module::Type`.
+    pub fn type_pointer(&self, path: &str) -> Option<String> {
+        scopes(self)
+            .iter()
+            .flat_map(scope_types)
+            .find(|(_, type_path, _)| type_path == path)
+            .map(|(pointer, _, _)| pointer)
+    }
+
+    /// Records the package in its `FromCode` constructors, whose templates use its partials.
+    pub(crate) fn set_template_package(&mut self) {
+        fn set(
+            types: &mut HashMap<String, Type>,
+            modules: &mut HashMap<String, Module>,
+            package: &(String, String),
+        ) {
+            for constructor in types.values_mut().flat_map(|t| t.constructors.values_mut()) {
+                if let Constructor::FromCode {
+                    package: template_package,
+                    ..
+                } = constructor
+                {
+                    *template_package = Some(package.clone());
+                }
+            }
+            for module in modules.values_mut() {
+                set(&mut module.types, &mut module.modules, package);
+            }
+        }
+
+        let package = (self.name.clone(), self.version.clone());
+        for krate in self.crates.values_mut() {
+            set(&mut krate.types, &mut krate.modules, &package);
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Crate {
     pub types: HashMap<String, Type>,
     pub modules: HashMap<String, Module>,
+    /// Where generated projects get the crate from. Required for crates used by flows that a
+    /// `Cargo.toml` is generated for, see `Flow::emit_cargo_toml`.
+    #[serde(default)]
+    pub dependency: Option<CrateDependency>,
     //Note: We do not allow sub-crates.
     //      All we care about are correct full qualified type names.
     //      And in Rust, parent crates are not part of the fqn of a type.
@@ -26,12 +99,41 @@ pub struct Crate {
 impl Crate {
     pub fn new_with_types(types: HashMap<String, Type>) -> Self {
         Self {
-            types: types,
+            types,
             modules: HashMap::new(),
+            dependency: None,
         }
     }
 }
 
+/// A `Cargo.toml` dependency, e.g. `{"version": "0.2", "features": ["serde"]}`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CrateDependency {
+    #[serde(flatten)]
+    pub source: CrateSource,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CrateSource {
+    Git {
+        git: String,
+        #[serde(default)]
+        branch: Option<String>,
+        #[serde(default)]
+        rev: Option<String>,
+    },
+    Path {
+        path: String,
+    },
+    /// A crates.io version requirement.
+    CratesIo {
+        version: String,
+    },
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Module {
     pub types: HashMap<String, Type>,
@@ -55,25 +157,108 @@ pub struct TypeParameter {
     pub name: String,
 
     // We only support trait constraints as is T::Default or T::Clone
+    // See `PackageManager::check_type_parameter_constraints`.
     #[serde(rename = "where")]
     pub constraints: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(remote = "Self")]
 pub struct Type {
     pub inputs: Option<HashMap<String, Input>>,
     pub outputs: Option<HashMap<String, Output>>,
     pub type_parameters: Option<Vec<TypeParameter>>,
     pub constructors: HashMap<String, Constructor>,
+    /// Traits the type implements, e.g. `Clone` or `std::fmt::Display`.
+    #[serde(default)]
+    pub traits: Vec<String>,
+}
+
+/// Type descriptions written in compact Rust syntax cannot tell generics from concrete types,
+/// so the declared type parameters are applied after deserialization.
+impl<'de> Deserialize<'de> for Type {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut t = Type::deserialize(deserializer)?;
+        t.mark_generics();
+        Ok(t)
+    }
+}
+
+impl Serialize for Type {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Type::serialize(self, serializer)
+    }
 }
 
 impl Type {
+    /// Marks all type descriptions naming a declared type parameter as generic.
+    pub fn mark_generics(&mut self) {
+        let names: Vec<String> = self
+            .type_parameters
+            .iter()
+            .flatten()
+            .map(|tp| tp.name.clone())
+            .collect();
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        if names.is_empty() {
+            return;
+        }
+
+        for input in self.inputs.iter_mut().flat_map(|i| i.values_mut()) {
+            input.input_type.mark_generics(&names);
+        }
+        for output in self.outputs.iter_mut().flat_map(|o| o.values_mut()) {
+            output.output_type.mark_generics(&names);
+        }
+        for constructor in self.constructors.values_mut() {
+            if let Constructor::NewWithArbitraryArgs { arguments, .. }
+            | Constructor::FromCode { arguments, .. } = constructor
+            {
+                for arg in arguments {
+                    arg.arg_type.mark_generics(&names);
+                }
+            }
+        }
+    }
+
+    /// The description of an object called `name` of this type, which is known as `type_name`,
+    /// with the type parameters bound as `type_parameters` says.
+    pub fn object_description(
+        &self,
+        type_name: &str,
+        name: &str,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<ObjectDescription, Error> {
+        let declared_parameters = self
+            .type_parameters
+            .iter()
+            .flatten()
+            .map(|tp| {
+                Box::new(TypeDescription::Generic {
+                    name: tp.name.clone(),
+                    type_parameters: None,
+                })
+            })
+            .collect();
+
+        Ok(ObjectDescription {
+            type_name: split_qualified_type_name(type_name).1.to_string(),
+            type_parameter_part: TypeDescription::emit_type_parameters_part(
+                &Some(declared_parameters),
+                type_parameters,
+            )?,
+            name: name.to_string(),
+            is_mutable: false,
+        })
+    }
+
     pub fn new_with_constructor(constructor_name: &str, constructor: Constructor) -> Self {
         let mut t = Self {
             inputs: Option::None,
             outputs: Option::None,
             type_parameters: Option::None,
             constructors: HashMap::new(),
+            traits: Vec::new(),
         };
         t.constructors.insert(constructor_name.into(), constructor);
         t
@@ -85,6 +270,7 @@ impl Type {
             outputs: Option::None,
             type_parameters: Option::None,
             constructors: HashMap::new(),
+            traits: Vec::new(),
         };
         t.constructors
             .insert("Default".into(), Constructor::FromDefault);
@@ -99,8 +285,37 @@ impl Type {
             outputs: Option::None,
             type_parameters: Option::None,
             constructors: HashMap::new(),
+            traits: Vec::new(),
         }
     }
+
+    /// Whether the type is registered to implement the trait. A `FromDefault` constructor
+    /// implies `Default` and a `FromJson` constructor implies `Deserialize`.
+    ///
+    /// Trait names match if they are equal or if one of them is unqualified and equals the last
+    /// segment of the other, e.g. `Debug` and `std::fmt::Debug`.
+    pub fn implements(&self, trait_name: &str) -> bool {
+        let implied = self.constructors.values().filter_map(|c| match c {
+            Constructor::FromDefault => Some("Default"),
+            Constructor::FromJson => Some("serde::Deserialize"),
+            _ => None,
+        });
+
+        self.traits
+            .iter()
+            .map(|t| t.as_str())
+            .chain(implied)
+            .any(|t| trait_names_match(t, trait_name))
+    }
+}
+
+pub fn trait_names_match(a: &str, b: &str) -> bool {
+    let last = |name: &str| name.rsplit("::").next().unwrap_or(name).to_string();
+    if a.contains("::") && b.contains("::") {
+        a == b
+    } else {
+        last(a) == last(b)
+    }
 }
 
 impl fmt::Display for Type {
@@ -117,28 +332,190 @@ impl FromStr for Type {
     }
 }
 
+/// Keywords that cannot be used as identifiers, see
+/// <https://doc.rust-lang.org/reference/keywords.html>.
+const KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Keywords that cannot be raw identifiers either.
+const NON_RAW_KEYWORDS: [&str; 4] = ["crate", "self", "Self", "super"];
+
+/// The identifiers of one generated scope, shared by all namespaces derived from the same root.
+#[derive(Debug, Default)]
+struct Identifiers {
+    by_path: HashMap<Vec<String>, String>,
+    paths: HashMap<String, Vec<String>>,
+    /// Identifiers of objects that exist outside of the generated code, e.g. `change_observer`.
+    reserved: Vec<String>,
+}
+
+impl Identifiers {
+    fn is_taken(&self, identifier: &str) -> bool {
+        self.paths.contains_key(identifier) || self.reserved.iter().any(|r| r == identifier)
+    }
+}
+
+/// The path of the object being constructed, e.g. `scale` -> `factor`.
+///
+/// Namespaces cloned from the same root hand out unique, valid identifiers for the objects of a
+/// generated scope: invalid characters are replaced, keywords are escaped as raw identifiers and
+/// colliding names (`a` -> `b_c` and `a_b` -> `c`) get a numeric suffix.
 #[derive(Debug, Clone)]
 pub struct Namespace {
     parts: Vec<String>,
+    identifiers: Rc<RefCell<Identifiers>>,
+    /// The `data` document, whose values `FromCode` templates get as `init_data`.
+    data: Option<Rc<Value>>,
+}
+
+impl Default for Namespace {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Namespace {
+    /// A new scope. `data`, `change_observer` and `context` are reserved, as generated code
+    /// refers to them.
     pub fn new() -> Self {
-        Self { parts: Vec::new() }
+        let ns = Self {
+            parts: Vec::new(),
+            identifiers: Rc::new(RefCell::new(Identifiers::default())),
+            data: None,
+        };
+        for name in ["data", "change_observer", "context"] {
+            ns.existing_name(name);
+        }
+        ns
     }
 
-    fn add_part(&mut self, part: &str) {
+    /// A new scope that also reserves the names of all context objects registered in `pack_man`.
+    pub fn with_context_objects(pack_man: &PackageManager) -> Self {
+        let ns = Self::new();
+        for name in pack_man.context_objects.keys() {
+            ns.existing_name(name);
+        }
+        ns
+    }
+
+    /// Makes the values of the `data` document available to `FromCode` templates as
+    /// `init_data`, see [`crate::flow::Flow::data_document`].
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(Rc::new(data));
+        self
+    }
+
+    /// The value of the object `name` in the `data` document, or null.
+    pub(crate) fn data_of(&self, name: &str) -> Value {
+        self.data
+            .as_deref()
+            .and_then(|data| {
+                self.parts
+                    .iter()
+                    .map(String::as_str)
+                    .chain([name])
+                    .try_fold(data, |value, key| value.get(key))
+            })
+            .cloned()
+            .unwrap_or(Value::Null)
+    }
+
+    pub(crate) fn add_part(&mut self, part: &str) {
         self.parts.push(part.to_string());
     }
 
-    fn is_empty(&self) -> bool {
-        self.parts.is_empty()
+    /// The identifier of the variable holding the object `name` in this namespace.
+    pub(crate) fn qualified_name(&self, name: &str) -> String {
+        let mut path = self.parts.clone();
+        path.push(name.to_string());
+
+        let mut identifiers = self.identifiers.borrow_mut();
+        if let Some(identifier) = identifiers.by_path.get(&path) {
+            return escape_keyword(identifier);
+        }
+
+        let candidate = path
+            .iter()
+            .map(|p| sanitize_identifier(p))
+            .collect::<Vec<String>>()
+            .join("_");
+        let mut identifier = candidate.clone();
+        let mut suffix = 2;
+        while identifiers.is_taken(&identifier) {
+            identifier = format!("{}_{}", candidate, suffix);
+            suffix += 1;
+        }
+
+        identifiers.by_path.insert(path.clone(), identifier.clone());
+        identifiers.paths.insert(identifier.clone(), path);
+        escape_keyword(&identifier)
+    }
+
+    /// The identifier of the shared object `name`, which lives in the root of the namespace.
+    pub(crate) fn shared_name(&self, name: &str) -> String {
+        Self {
+            parts: Vec::new(),
+            identifiers: self.identifiers.clone(),
+            data: self.data.clone(),
+        }
+        .qualified_name(name)
+    }
+
+    /// The identifier of an object that exists outside of the generated code. Generated objects
+    /// do not get this identifier afterwards.
+    pub(crate) fn existing_name(&self, name: &str) -> String {
+        let identifier = sanitize_identifier(name);
+        let mut identifiers = self.identifiers.borrow_mut();
+        if !identifiers.reserved.contains(&identifier) {
+            identifiers.reserved.push(identifier.clone());
+        }
+        escape_keyword(&identifier)
+    }
+
+    /// The namespace path of an object by its emitted identifier, e.g. for diagnostics.
+    pub fn path_of(&self, identifier: &str) -> Option<Vec<String>> {
+        let identifier = identifier.strip_prefix("r#").unwrap_or(identifier);
+        self.identifiers.borrow().paths.get(identifier).cloned()
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.parts.join("_"))
     }
 }
 
-impl ToString for Namespace {
-    fn to_string(&self) -> String {
-        self.parts.join("_")
+/// Replaces characters that are not allowed in identifiers and keywords that cannot be escaped.
+fn sanitize_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    if identifier == "_" || NON_RAW_KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+fn escape_keyword(identifier: &str) -> String {
+    if KEYWORDS.contains(&identifier) {
+        format!("r#{}", identifier)
+    } else {
+        identifier.to_string()
     }
 }
 
@@ -156,7 +533,11 @@ pub struct Modifier {
     is_reference: bool,
 }
 
+/// Describes a Rust type. In package JSON it can be written as object or in compact Rust syntax
+/// (e.g. `"Vec<Option<T>>"`), see [`TypeDescription::parse`].
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(remote = "Self")]
+#[allow(clippy::vec_box)]
 pub enum TypeDescription {
     Type {
         name: String,
@@ -167,12 +548,70 @@ pub enum TypeDescription {
         name: String,
         type_parameters: Option<Vec<Box<TypeDescription>>>,
     },
+
+    /// A lifetime argument, e.g. the `'a` in `Cow<'a, str>`. The name excludes the `'`.
+    Lifetime { name: String },
+
+    /// `&'a T` or `&mut T`.
+    Reference {
+        lifetime: Option<String>,
+        mutable: bool,
+        inner: Box<TypeDescription>,
+    },
+
+    /// `(A, B)`. The unit type is described as `Type` named `()`.
+    Tuple { elements: Vec<TypeDescription> },
+
+    /// `[T; N]`.
+    Array {
+        element: Box<TypeDescription>,
+        length: usize,
+    },
+
+    /// `[T]`.
+    Slice { element: Box<TypeDescription> },
+
+    /// `fn(A, B) -> R`.
+    FunctionPointer {
+        parameters: Vec<TypeDescription>,
+        return_type: Option<Box<TypeDescription>>,
+    },
+
+    /// `dyn Trait + 'a`.
+    TraitObject {
+        bounds: Vec<TraitBound>,
+        lifetime: Option<String>,
+    },
+}
+
+/// A trait of a [`TypeDescription::TraitObject`], e.g. `Send`, `Into<u8>` or `Fn(i32) -> i32`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TraitBound {
+    pub name: String,
+    pub type_parameters: Option<Vec<TypeDescription>>,
+    /// Parenthesized parameters of `Fn`, `FnMut` and `FnOnce` bounds.
+    pub parameters: Option<Vec<TypeDescription>>,
+    pub return_type: Option<Box<TypeDescription>>,
+}
+
+/// An object that exists outside of the generated code, e.g. `change_observer`. Constructors
+/// receive it through `ArgumentConstruction::ExistingObject` arguments named like it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContextObject {
+    #[serde(rename = "type")]
+    pub object_type: Box<TypeDescription>,
+    /// How `NewWithObserver` and `NewWithObserverAndContext` constructors pass the object.
+    pub passing: ArgumentPassing,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum ArgumentConstruction {
     Constructor(String),
+    /// The context object with the argument's name, see [`ContextObject`].
     ExistingObject(),
+    /// The shared object of the flow with this name, constructed once for all arguments
+    /// referencing it.
+    Shared(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -185,29 +624,148 @@ pub struct Argument {
 }
 
 impl Argument {
-    pub fn new_change_observer_arg() -> Self {
-        Self {
-            arg_type: Box::new(TypeDescription::Type {
-                name: "()".to_string(),
-                type_parameters: None,
-            }),
-            name: "change_observer".to_string(),
-            passing: ArgumentPassing::Clone,
+    /// The argument passing the registered context object `name` as it is declared.
+    pub fn new_context_object_arg(name: &str, pack_man: &PackageManager) -> Result<Self, Error> {
+        let context_object = pack_man.context_object(name)?;
+        Ok(Self {
+            arg_type: context_object.object_type.clone(),
+            name: name.to_string(),
+            passing: context_object.passing.clone(),
             construction: ArgumentConstruction::ExistingObject(),
+        })
+    }
+
+    /// Checks that the type of an `ExistingObject` argument is the type of the registered
+    /// context object.
+    pub(crate) fn check_context_object(
+        &self,
+        pack_man: &PackageManager,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        let context_object = pack_man.context_object(&self.name)?;
+        let expected = context_object.object_type.emit_rust_type(&HashMap::new())?;
+        let found = self.arg_type.emit_rust_type(type_parameters)?;
+        if expected != found {
+            return Err(Error::msg(format!(
+                "Argument '{}' has type '{}', but context object '{}' is '{}'.",
+                self.name, found, self.name, expected
+            )));
         }
+        Ok(())
     }
 
-    pub fn new_context_arg() -> Self {
-        Self {
-            arg_type: Box::new(TypeDescription::Type {
-                name: "()".to_string(),
-                type_parameters: None,
-            }),
-            name: "context".to_string(),
-            passing: ArgumentPassing::Clone,
-            construction: ArgumentConstruction::ExistingObject(),
+    /// The name the argument's type is looked up with, the emitted type name and its emitted
+    /// type parameters.
+    pub(crate) fn type_names(
+        &self,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<(String, String, String), Error> {
+        Ok(match self.arg_type.as_ref() {
+            TypeDescription::Type {
+                name,
+                type_parameters: arg_type_parameters,
+            } => (
+                name.clone(),
+                split_qualified_type_name(name).1.to_string(),
+                TypeDescription::emit_type_parameters_part(arg_type_parameters, type_parameters)?,
+            ),
+
+            TypeDescription::Generic {
+                name,
+                type_parameters: arg_type_parameters,
+            } => {
+                // check if generic was already resolved. if so, try to get type and emit constructor code.
+                // TODO: Think about what should happen if it is not yet resolved.
+                let Some(type_name) = type_parameters.get(name) else {
+                    return Err(Error::msg("Generic type was not resolved"));
+                };
+                (
+                    type_name.clone(),
+                    split_qualified_type_name(type_name).1.to_string(),
+                    TypeDescription::emit_type_parameters_part(
+                        arg_type_parameters,
+                        type_parameters,
+                    )?,
+                )
+            }
+
+            // Tuples, arrays etc. are looked up by their Rust syntax, see `PackageManager::resolve_type`.
+            structural => {
+                let rust_type = structural.emit_rust_type(type_parameters)?;
+                (rust_type.clone(), rust_type, "".to_string())
+            }
+        })
+    }
+
+    /// The constructor of the argument's type called `constructor_name`, the description of the
+    /// object it creates and the bindings of the type parameters of the argument's type, e.g.
+    /// `T` -> `u8` for `Wrapper<u8>`, which the constructor is emitted with.
+    pub(crate) fn constructor<'a>(
+        &self,
+        constructor_name: &str,
+        pack_man: &'a PackageManager,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<(&'a Constructor, ObjectDescription, HashMap<String, String>), Error> {
+        let (lookup_name, type_name, type_parameter_part) = self.type_names(type_parameters)?;
+
+        let resolved = pack_man.resolve_type(&lookup_name)?;
+        let constructor = resolved
+            .type_desc
+            .constructors
+            .get(constructor_name)
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "Constructor '{}' for type '{}' not found.",
+                    constructor_name, lookup_name
+                ))
+            })?;
+        Ok((
+            constructor,
+            self.to_object_description(&type_name, &type_parameter_part),
+            Self::type_bindings(
+                resolved.type_desc,
+                &format!("{}{}", lookup_name, type_parameter_part),
+                type_parameters,
+            )?,
+        ))
+    }
+
+    /// Binds the type parameters `type_desc` declares to the type arguments of `rust_type`.
+    /// Types written without type arguments keep the bindings of the object they are passed to.
+    fn type_bindings(
+        type_desc: &Type,
+        rust_type: &str,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, Error> {
+        let declared: Vec<&TypeParameter> = type_desc.type_parameters.iter().flatten().collect();
+        let arguments: Vec<TypeDescription> = match TypeDescription::from_str(rust_type) {
+            Ok(TypeDescription::Type {
+                type_parameters: Some(arguments),
+                ..
+            }) => arguments
+                .into_iter()
+                .map(|argument| *argument)
+                .filter(|argument| !matches!(argument, TypeDescription::Lifetime { .. }))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if arguments.is_empty() {
+            return Ok(type_parameters.clone());
+        }
+        if arguments.len() != declared.len() {
+            return Err(Error::msg(format!(
+                "Type '{}' needs {} type arguments.",
+                rust_type,
+                declared.len()
+            )));
         }
+        declared
+            .into_iter()
+            .zip(arguments)
+            .map(|(tp, argument)| Ok((tp.name.clone(), argument.emit_rust_type(&HashMap::new())?)))
+            .collect()
     }
+
     fn emit_prefix_code(&self) -> String {
         match self.passing {
             ArgumentPassing::Move => "".to_string(),
@@ -226,21 +784,17 @@ impl Argument {
         }
     }
 
-    fn into_object_description(
+    fn to_object_description(
         &self,
-        type_name: &String,
-        type_parameter_part: &String,
+        type_name: &str,
+        type_parameter_part: &str,
     ) -> ObjectDescription {
         ObjectDescription {
-            type_name: type_name.clone(),
-            type_parameter_part: type_parameter_part.clone(),
+            type_name: type_name.to_string(),
+            type_parameter_part: type_parameter_part.to_string(),
             //type_parameters: HashMap::new(),
             name: self.name.clone(),
-            is_mutable: if let ArgumentPassing::MutableReference { .. } = self.passing {
-                true
-            } else {
-                false
-            },
+            is_mutable: matches!(self.passing, ArgumentPassing::MutableReference),
         }
     }
 }
@@ -254,53 +808,259 @@ pub struct ObjectDescription {
     pub is_mutable: bool,
 }
 
+/// A value the generated code reads from the `data` document with `serde_json::from_value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRead {
+    /// Name of the variable the value is assigned to, e.g. `scale_factor`.
+    pub name: String,
+    /// Keys from the document root, e.g. `["scale", "factor"]` for `data["scale"]["factor"]`.
+    pub path: Vec<String>,
+    /// The emitted Rust type of the value, e.g. `f32` or `my_crate::Params<i32>`.
+    pub rust_type: String,
+    /// Whether a template reads the value through `init_data` while the code is emitted, so the
+    /// generated code does not need the document.
+    pub at_generation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataIssueKind {
+    /// The generated code reads a key the document does not contain.
+    Missing,
+    /// The value cannot be deserialized as the primitive (or tuple or array of primitives) the
+    /// code reads.
+    WrongKind { expected: String, found: String },
+    /// No generated code reads the key.
+    Unused,
+}
+
+/// A problem of a `data` document, see [`validate_data`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataIssue {
+    /// Name of the variable read from the path, or the path joined like one if nothing reads it.
+    pub name: String,
+    pub path: Vec<String>,
+    pub kind: DataIssueKind,
+}
+
+impl fmt::Display for DataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path: String = self.path.iter().map(|p| format!("[\"{}\"]", p)).collect();
+        match &self.kind {
+            DataIssueKind::Missing => write!(f, "'{}': data{} is missing.", self.name, path),
+            DataIssueKind::WrongKind { expected, found } => write!(
+                f,
+                "'{}': data{} is {} but must be {}.",
+                self.name, path, found, expected
+            ),
+            DataIssueKind::Unused => write!(f, "'{}': data{} is not used.", self.name, path),
+        }
+    }
+}
+
+/// Checks a `data` document against the values the generated code reads from it.
+///
+/// Only built-in types and tuples and arrays of them are checked beyond presence.
+pub fn validate_data(
+    reads: &[JsonRead],
+    data: &Value,
+    pack_man: &PackageManager,
+) -> Vec<DataIssue> {
+    let mut issues = Vec::new();
+
+    for read in reads {
+        let value = read.path.iter().try_fold(data, |value, key| {
+            value.as_object().and_then(|o| o.get(key))
+        });
+        let kind = match value {
+            None => Some(DataIssueKind::Missing),
+            Some(value) => TypeDescription::from_str(&read.rust_type)
+                .ok()
+                .and_then(|td| check_data_kind(&td, value, pack_man)),
+        };
+        if let Some(kind) = kind {
+            issues.push(DataIssue {
+                name: read.name.clone(),
+                path: read.path.clone(),
+                kind,
+            });
+        }
+    }
+
+    collect_unused_data(reads, data, &mut Vec::new(), &mut issues);
+    issues
+}
+
+/// `Some(DataIssueKind::WrongKind)` if the value does not fit the type.
+fn check_data_kind(
+    td: &TypeDescription,
+    value: &Value,
+    pack_man: &PackageManager,
+) -> Option<DataIssueKind> {
+    let fits = match td {
+        TypeDescription::Type {
+            name,
+            type_parameters: None,
+        } if pack_man.is_built_in_type(name) => match name.as_str() {
+            "bool" => value.is_boolean(),
+            "char" => value.as_str().is_some_and(|s| s.chars().count() == 1),
+            "f32" | "f64" => value.is_number(),
+            "()" => value.is_null(),
+            "i8" => value.as_i64().is_some_and(|i| i8::try_from(i).is_ok()),
+            "i16" => value.as_i64().is_some_and(|i| i16::try_from(i).is_ok()),
+            "i32" => value.as_i64().is_some_and(|i| i32::try_from(i).is_ok()),
+            "i64" | "isize" => value.as_i64().is_some(),
+            "u8" => value.as_u64().is_some_and(|i| u8::try_from(i).is_ok()),
+            "u16" => value.as_u64().is_some_and(|i| u16::try_from(i).is_ok()),
+            "u32" => value.as_u64().is_some_and(|i| u32::try_from(i).is_ok()),
+            "u64" | "usize" | "u128" => value.as_u64().is_some(),
+            // Every integer serde_json parses fits.
+            "i128" => value.is_i64() || value.is_u64(),
+            _ => false,
+        },
+
+        TypeDescription::Tuple { elements } if elements.is_empty() => value.is_null(),
+        TypeDescription::Tuple { elements } => value.as_array().is_some_and(|values| {
+            values.len() == elements.len()
+                && elements
+                    .iter()
+                    .zip(values)
+                    .all(|(td, v)| check_data_kind(td, v, pack_man).is_none())
+        }),
+
+        TypeDescription::Array { element, length } => value.as_array().is_some_and(|values| {
+            values.len() == *length
+                && values
+                    .iter()
+                    .all(|v| check_data_kind(element, v, pack_man).is_none())
+        }),
+
+        // Anything else is deserialized by its own `Deserialize` implementation.
+        _ => true,
+    };
+
+    (!fits).then(|| DataIssueKind::WrongKind {
+        expected: td.to_string(),
+        found: data_kind_name(value),
+    })
+}
+
+fn data_kind_name(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "a boolean".to_string(),
+        Value::Number(n) => format!("the number {}", n),
+        Value::String(s) => format!("the string {:?}", s),
+        Value::Array(a) => format!("an array of length {}", a.len()),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+fn collect_unused_data(
+    reads: &[JsonRead],
+    value: &Value,
+    path: &mut Vec<String>,
+    issues: &mut Vec<DataIssue>,
+) {
+    let Some(object) = value.as_object() else {
+        return;
+    };
+    let mut keys: Vec<&String> = object.keys().collect();
+    keys.sort();
+    for key in keys {
+        path.push(key.clone());
+        if reads.iter().any(|r| r.path == *path) {
+            // Read as a whole.
+        } else if reads.iter().any(|r| r.path.starts_with(path)) {
+            collect_unused_data(reads, &object[key], path, issues);
+        } else {
+            issues.push(DataIssue {
+                name: path.join("_"),
+                path: path.clone(),
+                kind: DataIssueKind::Unused,
+            });
+        }
+        path.pop();
+    }
+}
+
+/// How emitted code handles constructions that fail at runtime: creating objects from Json and
+/// calling fallible constructors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ErrorHandling {
+    /// Panics with `expect`.
+    #[default]
+    Panic,
+    /// Propagates errors with `?`. The code must be placed in a function returning a `Result`
+    /// whose error type all errors convert into, e.g. `Box<dyn std::error::Error>`.
+    Propagate,
+}
+
+/// `fallible` marks constructors returning `Result` instead of the object.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Constructor {
     New {
         function_name: Option<String>,
+        #[serde(default)]
+        fallible: bool,
     },
     NewWithObserver {
         function_name: Option<String>,
+        #[serde(default)]
+        fallible: bool,
     },
     NewWithObserverAndContext {
         function_name: Option<String>,
+        #[serde(default)]
+        fallible: bool,
     },
     NewWithArbitraryArgs {
         function_name: Option<String>,
         arguments: Vec<Argument>,
+        #[serde(default)]
+        fallible: bool,
     },
     FromJson,
     FromDefault,
+    /// Renders `code_template` with the `TemplateRegistry` of the package manager after
+    /// constructing the `arguments`. The template refers to them as `{{arguments.<name>}}`, and
+    /// to the objects it constructs with the `construct` helper as well.
     FromCode {
         code_template: String,
+        #[serde(default)]
+        arguments: Vec<Argument>,
+        /// Name and version of the package declaring the constructor, set when the package is
+        /// registered.
+        #[serde(skip)]
+        package: Option<(String, String)>,
     },
 }
 
 impl Constructor {
-    fn emit_fully_qualified_name(
-        &self,
-        name: &String,
-        namespace: &Namespace,
-        ignore: bool,
-    ) -> String {
-        if namespace.is_empty() || ignore {
-            name.clone()
+    fn emit_fully_qualified_name(&self, name: &str, namespace: &Namespace, ignore: bool) -> String {
+        if ignore {
+            namespace.existing_name(name)
         } else {
-            format!("{}_{}", namespace.to_string(), name)
+            namespace.qualified_name(name)
         }
     }
 
-    fn emit_args(&self, args: &Vec<Argument>, current_namespace: &Namespace) -> String {
+    fn emit_args(&self, args: &[Argument], current_namespace: &Namespace) -> String {
         args.iter()
             .map(|arg| {
-                format!(
-                    "{}{}{}",
-                    arg.emit_prefix_code(),
-                    self.emit_fully_qualified_name(
+                let name = match &arg.construction {
+                    ArgumentConstruction::Shared(shared_name) => {
+                        current_namespace.shared_name(shared_name)
+                    }
+                    construction => self.emit_fully_qualified_name(
                         &arg.name,
                         current_namespace,
-                        matches!(arg.construction, ArgumentConstruction::ExistingObject())
+                        matches!(construction, ArgumentConstruction::ExistingObject()),
                     ),
+                };
+                format!(
+                    "{}{}{}",
+                    arg.emit_prefix_code(),
+                    name,
                     arg.emit_postfix_code()
                 )
             })
@@ -308,74 +1068,6 @@ impl Constructor {
             .join(", ")
     }
 
-    fn emit_arg_type_parameters_part_rec(
-        &self,
-        tp_part: &mut String,
-        argument_type: &Box<TypeDescription>,
-        resolved_type_parameters: &HashMap<String, String>,
-    ) {
-        let arg_type_params: &Option<Vec<Box<TypeDescription>>>;
-
-        match argument_type.as_ref() {
-            TypeDescription::Type {
-                name,
-                type_parameters,
-            } => {
-                tp_part.push_str(name);
-                arg_type_params = type_parameters;
-            }
-
-            TypeDescription::Generic {
-                name,
-                type_parameters,
-            } => {
-                if let Some(tn) = resolved_type_parameters.get(name) {
-                    tp_part.push_str(tn);
-                } else {
-                    //TODO: Error Handling
-                }
-                arg_type_params = type_parameters;
-            }
-        }
-
-        if let Some(params) = arg_type_params {
-            if !params.is_empty() {
-                tp_part.push_str("<");
-
-                for tp in params {
-                    self.emit_arg_type_parameters_part_rec(tp_part, &tp, resolved_type_parameters);
-                    tp_part.push_str(",")
-                }
-
-                tp_part.pop(); // pop last ,
-                tp_part.push_str(">");
-            }
-        }
-    }
-
-    fn emit_arg_type_parameters_part(
-        &self,
-        arg_type_parameters: &Option<Vec<Box<TypeDescription>>>,
-        type_parameters: &HashMap<String, String>,
-    ) -> String {
-        if let Some(arg_type_params) = arg_type_parameters {
-            if !arg_type_params.is_empty() {
-                let mut tp_part = "<".to_string();
-
-                for tp in arg_type_params {
-                    self.emit_arg_type_parameters_part_rec(&mut tp_part, tp, type_parameters);
-                    tp_part.push_str(",")
-                }
-
-                //tp_part.pop(); // pop last ,
-                tp_part.push_str(">");
-                return tp_part;
-            }
-        }
-
-        "".to_string()
-    }
-
     fn emit_arg_construction_code(
         &self,
         arg: &Argument,
@@ -383,85 +1075,18 @@ impl Constructor {
         pack_man: &PackageManager,
         current_namespace: &Namespace,
         type_parameters: &HashMap<String, String>,
+        error_handling: ErrorHandling,
     ) -> Result<String, Error> {
-        match arg.arg_type.as_ref() {
-            TypeDescription::Type {
-                name,
-                type_parameters: arg_type_parameters,
-            } => {
-                if let Some(type_desc) = pack_man.get_type(name) {
-                    if let Some(arg_constructor) = type_desc.constructors.get(&arg_constructor_name)
-                    {
-                        let object_desc = arg.into_object_description(
-                            &name,
-                            &self.emit_arg_type_parameters_part(
-                                arg_type_parameters,
-                                type_parameters,
-                            ),
-                        );
-
-                        arg_constructor.emit_code_template(
-                            &object_desc,
-                            type_parameters,
-                            pack_man,
-                            current_namespace,
-                        )
-                    } else {
-                        Err(Error::msg(format!(
-                            "Constructor '{}' for type '{}' not found.",
-                            arg_constructor_name, name
-                        )))
-                    }
-                } else {
-                    Err(Error::msg(format!(
-                        "Type description for '{}' not found.",
-                        name
-                    )))
-                }
-            }
-
-            TypeDescription::Generic {
-                name,
-                type_parameters: arg_type_parameters,
-            } => {
-                // check if generic was already resolved. if so, try to get type and emit constructor code.
-                // TODO: Think about what should happen if it is not yet resolved.
-                if let Some(type_name) = type_parameters.get(name) {
-                    if let Some(type_desc) = pack_man.get_type(&type_name) {
-                        if let Some(arg_constructor) =
-                            type_desc.constructors.get(&arg_constructor_name)
-                        {
-                            let object_desc = arg.into_object_description(
-                                &type_name,
-                                &self.emit_arg_type_parameters_part(
-                                    arg_type_parameters,
-                                    &type_parameters,
-                                ),
-                            );
-
-                            arg_constructor.emit_code_template(
-                                &object_desc,
-                                &type_parameters,
-                                pack_man,
-                                current_namespace,
-                            )
-                        } else {
-                            Err(Error::msg(format!(
-                                "Constructor '{}' for type '{}' not found.",
-                                arg_constructor_name, name
-                            )))
-                        }
-                    } else {
-                        Err(Error::msg(format!(
-                            "Type description for '{}' not found",
-                            type_name
-                        )))
-                    }
-                } else {
-                    Err(Error::msg("Generic type was not resolved"))
-                }
-            }
-        }
+        let (arg_constructor, object_desc, arg_type_parameters) =
+            arg.constructor(&arg_constructor_name, pack_man, type_parameters)?;
+
+        arg_constructor.emit_code_template_with(
+            &object_desc,
+            &arg_type_parameters,
+            pack_man,
+            current_namespace,
+            error_handling,
+        )
     }
 
     fn emit_args_construction_code(
@@ -470,6 +1095,7 @@ impl Constructor {
         args: &Vec<Argument>,
         current_namespace: &Namespace,
         type_parameters: &HashMap<String, String>,
+        error_handling: ErrorHandling,
     ) -> Result<String, Error> {
         let mut construction_blocks = Vec::<String>::new();
 
@@ -483,6 +1109,7 @@ impl Constructor {
                     pack_man,
                     current_namespace,
                     type_parameters,
+                    error_handling,
                 ) {
                     Ok(code) => construction_blocks.push(code),
                     Err(err) => return Err(err),
@@ -501,6 +1128,98 @@ impl Constructor {
         }
     }
 
+    /// The function name and fallibility of `New*` constructors.
+    fn function(&self) -> (&Option<String>, bool) {
+        match self {
+            Self::New {
+                function_name,
+                fallible,
+            }
+            | Self::NewWithObserver {
+                function_name,
+                fallible,
+            }
+            | Self::NewWithObserverAndContext {
+                function_name,
+                fallible,
+            }
+            | Self::NewWithArbitraryArgs {
+                function_name,
+                fallible,
+                ..
+            } => (function_name, *fallible),
+            _ => (&None, false),
+        }
+    }
+
+    /// The arguments `New*` constructors are called with and `FromCode` templates are rendered
+    /// with, context object arguments checked against the registered ones.
+    pub(crate) fn arguments(
+        &self,
+        pack_man: &PackageManager,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<Vec<Argument>, Error> {
+        Ok(match self {
+            Self::NewWithObserver { .. } => vec![Argument::new_context_object_arg(
+                "change_observer",
+                pack_man,
+            )?],
+            Self::NewWithObserverAndContext { .. } => vec![
+                Argument::new_context_object_arg("change_observer", pack_man)?,
+                Argument::new_context_object_arg("context", pack_man)?,
+            ],
+            Self::NewWithArbitraryArgs { arguments, .. } => {
+                Self::checked_arguments(arguments, pack_man, type_parameters)?
+            }
+            Self::FromCode {
+                code_template,
+                arguments,
+                ..
+            } => {
+                let mut arguments = Self::checked_arguments(arguments, pack_man, type_parameters)?;
+                let names: Vec<&str> = type_parameters.keys().map(String::as_str).collect();
+                for construct in self.template_uses(code_template, pack_man)?.constructs {
+                    let mut arg_type = TypeDescription::from_str(&construct.type_name)?;
+                    arg_type.mark_generics(&names);
+                    arguments.push(Argument {
+                        arg_type: Box::new(arg_type),
+                        name: construct.name,
+                        passing: ArgumentPassing::Move,
+                        construction: ArgumentConstruction::Constructor(construct.constructor),
+                    });
+                }
+                arguments
+            }
+            _ => Vec::new(),
+        })
+    }
+
+    fn checked_arguments(
+        arguments: &[Argument],
+        pack_man: &PackageManager,
+        type_parameters: &HashMap<String, String>,
+    ) -> Result<Vec<Argument>, Error> {
+        for arg in arguments {
+            if arg.construction == ArgumentConstruction::ExistingObject() {
+                arg.check_context_object(pack_man, type_parameters)?;
+            }
+        }
+        Ok(arguments.to_vec())
+    }
+
+    /// What a `FromCode` template reads through helpers, with the partials of its package.
+    fn template_uses(
+        &self,
+        code_template: &str,
+        pack_man: &PackageManager,
+    ) -> Result<TemplateUses, Error> {
+        let no_partials = HashMap::new();
+        let partials = pack_man
+            .package_of(self)
+            .map_or(&no_partials, |package| &package.partials);
+        template_uses(code_template, partials)
+    }
+
     fn emit_function_name(&self, function_name: &Option<String>) -> String {
         if let Some(func_name) = function_name {
             func_name.clone()
@@ -509,26 +1228,42 @@ impl Constructor {
         }
     }
 
+    /// Emits what follows a call that returns a `Result` of the object `name`.
+    fn emit_error_handling(&self, name: &str, error_handling: ErrorHandling) -> String {
+        match error_handling {
+            ErrorHandling::Panic => format!(".expect(\"Could not create '{}'.\")", name),
+            ErrorHandling::Propagate => "?".to_string(),
+        }
+    }
+
     fn emit_new_with_args(
         &self,
         od: &ObjectDescription,
         type_parameters: &HashMap<String, String>,
-        function_name: &Option<String>,
         pack_man: &PackageManager,
         args: &Vec<Argument>,
         current_namespace: &Namespace,
+        error_handling: ErrorHandling,
     ) -> Result<String, Error> {
+        let (function_name, fallible) = self.function();
         let mut new_namespace = current_namespace.clone();
         new_namespace.add_part(&od.name);
 
-        let args_construction_code =
-            self.emit_args_construction_code(pack_man, args, &new_namespace, type_parameters)?;
+        let args_construction_code = self.emit_args_construction_code(
+            pack_man,
+            args,
+            &new_namespace,
+            type_parameters,
+            error_handling,
+        )?;
 
+        let fully_qualified_name =
+            self.emit_fully_qualified_name(&od.name, current_namespace, false);
         Ok(format!(
-            "{}\nlet{} {} = {}::{}{}({});",
+            "{}\nlet{} {} = {}::{}{}({}){};",
             args_construction_code,
             self.emit_mutable(od.is_mutable),
-            self.emit_fully_qualified_name(&od.name, current_namespace, false),
+            fully_qualified_name,
             od.type_name,
             if od.type_parameter_part.is_empty() {
                 "".to_string()
@@ -536,7 +1271,12 @@ impl Constructor {
                 od.type_parameter_part.clone() + "::"
             },
             self.emit_function_name(function_name),
-            self.emit_args(args, &new_namespace)
+            self.emit_args(args, &new_namespace),
+            if fallible {
+                self.emit_error_handling(&fully_qualified_name, error_handling)
+            } else {
+                "".to_string()
+            }
         ))
     }
 
@@ -555,66 +1295,117 @@ impl Constructor {
         ))
     }
 
+    /// The keys as Rust string literals, e.g. `["scale"]["factor"]`.
     fn emit_json_path(&self, cn: &Namespace, od: &ObjectDescription) -> String {
-        format!(
-            "{}[\"{}\"]",
-            cn.parts
-                .iter()
-                .map(|item| format!("[\"{}\"]", item))
-                .collect::<Vec<String>>()
-                .join(""),
-            od.name
-        )
+        cn.parts
+            .iter()
+            .chain([&od.name])
+            .map(|key| format!("[{:?}]", key))
+            .collect()
     }
 
     fn emit_new_from_json(
         &self,
         od: &ObjectDescription,
         current_namespace: &Namespace,
+        error_handling: ErrorHandling,
     ) -> Result<String, Error> {
         let emit_fully_qualified_name =
             self.emit_fully_qualified_name(&od.name, current_namespace, false);
 
         Ok(format!(
-            "let{} {}: {}{} = serde_json::from_value(data{}.clone()).expect(\"Could not create '{}' from Json.\");",
+            "let{} {}: {}{} = serde_json::from_value(data{}.clone()){};",
             self.emit_mutable(od.is_mutable),
             emit_fully_qualified_name,
             od.type_name,
             od.type_parameter_part,
             self.emit_json_path(current_namespace, od),
-            emit_fully_qualified_name
+            match error_handling {
+                ErrorHandling::Panic => format!(
+                    ".expect(\"Could not create '{}' from Json.\")",
+                    emit_fully_qualified_name
+                ),
+                ErrorHandling::Propagate => "?".to_string(),
+            }
         ))
     }
 
-    fn emit_constructor_from_code(
+    pub(crate) fn emit_constructor_from_code(
         &self,
         od: &ObjectDescription,
         current_namespace: &Namespace,
-        code_template: &String,
+        code_template: &str,
         type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        error_handling: ErrorHandling,
     ) -> Result<String, Error> {
-        let mut handlebars = Handlebars::new();
+        let mut new_namespace = current_namespace.clone();
+        new_namespace.add_part(&od.name);
 
-        handlebars.register_template_string("code", code_template)?;
+        let args = self.arguments(pack_man, type_parameters)?;
+        let args_construction_code = self.emit_args_construction_code(
+            pack_man,
+            &args,
+            &new_namespace,
+            type_parameters,
+            error_handling,
+        )?;
 
-        let mut data = HashMap::new();
+        let mut data = serde_json::Map::new();
         data.insert(
             "fully_qualified_name".to_string(),
-            self.emit_fully_qualified_name(&od.name, current_namespace, false),
+            Value::String(self.emit_fully_qualified_name(&od.name, current_namespace, false)),
         );
-        data.insert("type_name".to_string(), od.type_name.clone());
+        data.insert("type_name".to_string(), Value::String(od.type_name.clone()));
         data.insert(
             "type_parameter_part".to_string(),
-            od.type_parameter_part.clone(),
+            Value::String(od.type_parameter_part.clone()),
+        );
+        data.insert(
+            "mutable".to_string(),
+            Value::String(self.emit_mutable(od.is_mutable)),
         );
-        data.insert("mutable".to_string(), self.emit_mutable(od.is_mutable));
         for (param, resolved_param) in type_parameters {
-            data.insert(format!("type_parameter_{}", param), resolved_param.clone());
+            data.insert(
+                format!("type_parameter_{}", param),
+                Value::String(resolved_param.clone()),
+            );
         }
+        data.insert(
+            "arguments".to_string(),
+            Value::Object(
+                args.iter()
+                    .map(|arg| {
+                        (
+                            arg.name.clone(),
+                            Value::String(
+                                self.emit_args(std::slice::from_ref(arg), &new_namespace),
+                            ),
+                        )
+                    })
+                    .collect(),
+            ),
+        );
+        data.insert("init_data".to_string(), current_namespace.data_of(&od.name));
+        data.insert(
+            "path".to_string(),
+            Value::Array(
+                current_namespace
+                    .parts
+                    .iter()
+                    .chain([&od.name])
+                    .map(|key| Value::String(key.clone()))
+                    .collect(),
+            ),
+        );
 
-        match handlebars.render("code", &data) {
-            Ok(res) => Ok(res),
-            Err(err) => Err(err.into()),
+        let code = pack_man
+            .templates
+            .render(code_template, &data, pack_man.package_of(self))?;
+        if args_construction_code.is_empty() {
+            Ok(code)
+        } else {
+            Ok(format!("{}\n{}", args_construction_code, code))
         }
     }
 }
@@ -627,57 +1418,172 @@ impl Constructor {
         pack_man: &PackageManager,
         namespace: &Namespace,
     ) -> Result<String, Error> {
+        self.emit_code_template_with(
+            obj_desc,
+            type_parameters,
+            pack_man,
+            namespace,
+            ErrorHandling::Panic,
+        )
+    }
+
+    /// Like `emit_code_template`, but with failing constructions handled as `error_handling` says.
+    pub fn emit_code_template_with(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        namespace: &Namespace,
+        error_handling: ErrorHandling,
+    ) -> Result<String, Error> {
+        self.check_constraints(obj_desc, type_parameters, pack_man)?;
+
         match self {
-            Self::New { function_name } => self.emit_new_with_args(
+            Self::New { .. }
+            | Self::NewWithObserver { .. }
+            | Self::NewWithObserverAndContext { .. }
+            | Self::NewWithArbitraryArgs { .. } => self.emit_new_with_args(
                 obj_desc,
                 type_parameters,
-                function_name,
                 pack_man,
-                &vec![],
+                &self.arguments(pack_man, type_parameters)?,
                 namespace,
+                error_handling,
             ),
 
-            Self::NewWithObserver { function_name } => self.emit_new_with_args(
-                obj_desc,
-                type_parameters,
-                function_name,
-                pack_man,
-                &vec![Argument::new_change_observer_arg()],
-                namespace,
-            ),
+            Self::FromJson => self.emit_new_from_json(obj_desc, namespace, error_handling),
 
-            Self::NewWithObserverAndContext { function_name } => self.emit_new_with_args(
-                obj_desc,
-                type_parameters,
-                function_name,
-                pack_man,
-                &vec![
-                    Argument::new_change_observer_arg(),
-                    Argument::new_context_arg(),
-                ],
-                namespace,
-            ),
+            Self::FromDefault => self.emit_default(obj_desc, pack_man, namespace),
 
-            Self::NewWithArbitraryArgs {
-                function_name,
-                arguments,
-            } => self.emit_new_with_args(
+            Self::FromCode { code_template, .. } => self.emit_constructor_from_code(
                 obj_desc,
+                namespace,
+                code_template,
                 type_parameters,
-                function_name,
                 pack_man,
-                arguments,
-                namespace,
+                error_handling,
             ),
+        }
+    }
 
-            Self::FromJson => self.emit_new_from_json(obj_desc, namespace),
+    /// Checks the type parameters of the object's type against their `where` constraints.
+    fn check_constraints(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+    ) -> Result<(), Error> {
+        // Types that cannot be looked up (e.g. not registered top-level types) are not checked.
+        if let Ok(resolved) = pack_man.resolve_type(&obj_desc.type_name) {
+            pack_man
+                .check_type_parameter_constraints(resolved.type_desc, type_parameters)
+                .map_err(|violations| {
+                    Error::msg(
+                        violations
+                            .iter()
+                            .map(|v| v.to_string())
+                            .collect::<Vec<String>>()
+                            .join(" "),
+                    )
+                })?;
+        }
+        Ok(())
+    }
 
-            Self::FromDefault => self.emit_default(obj_desc, pack_man, namespace),
+    /// Walks the constructors `emit_code_template` emits and collects the values their code
+    /// reads from the `data` document.
+    pub fn json_reads(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        namespace: &Namespace,
+    ) -> Result<Vec<JsonRead>, Error> {
+        let mut reads = Vec::new();
+        self.collect_json_reads(obj_desc, type_parameters, pack_man, namespace, &mut reads)?;
+        Ok(reads)
+    }
+
+    /// Checks a `data` document against the values the code `emit_code_template` emits for the
+    /// object reads from it. Keys not read by this object are reported as unused.
+    pub fn validate_data(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        namespace: &Namespace,
+        data: &Value,
+    ) -> Result<Vec<DataIssue>, Error> {
+        let reads = self.json_reads(obj_desc, type_parameters, pack_man, namespace)?;
+        Ok(validate_data(&reads, data, pack_man))
+    }
 
-            Self::FromCode { code_template } => {
-                self.emit_constructor_from_code(obj_desc, namespace, code_template, type_parameters)
+    fn collect_json_reads(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        namespace: &Namespace,
+        reads: &mut Vec<JsonRead>,
+    ) -> Result<(), Error> {
+        match self {
+            Self::FromJson => {
+                let mut path = namespace.parts.clone();
+                path.push(obj_desc.name.clone());
+                reads.push(JsonRead {
+                    name: self.emit_fully_qualified_name(&obj_desc.name, namespace, false),
+                    path,
+                    rust_type: format!("{}{}", obj_desc.type_name, obj_desc.type_parameter_part),
+                    at_generation: false,
+                });
             }
+
+            Self::NewWithArbitraryArgs { .. } | Self::FromCode { .. } => {
+                let mut new_namespace = namespace.clone();
+                new_namespace.add_part(&obj_desc.name);
+
+                for arg in self.arguments(pack_man, type_parameters)? {
+                    if let ArgumentConstruction::Constructor(constructor_name) = &arg.construction {
+                        let (arg_constructor, arg_desc, arg_type_parameters) =
+                            arg.constructor(constructor_name, pack_man, type_parameters)?;
+                        arg_constructor.collect_json_reads(
+                            &arg_desc,
+                            &arg_type_parameters,
+                            pack_man,
+                            &new_namespace,
+                            reads,
+                        )?;
+                    }
+                }
+
+                // Keys `json_path` gets from other expressions are unknown, so everything
+                // below the literal ones is read.
+                if let Self::FromCode { code_template, .. } = self {
+                    let name = self.emit_fully_qualified_name(&obj_desc.name, namespace, false);
+                    let uses = self.template_uses(code_template, pack_man)?;
+                    let json_paths = uses.json_paths.into_iter().map(|keys| (keys, false));
+                    let init_data = uses.init_data.into_iter().map(|keys| (keys, true));
+                    for (keys, at_generation) in json_paths.chain(init_data) {
+                        let mut path = new_namespace.parts.clone();
+                        path.extend(keys.iter().cloned());
+                        reads.push(JsonRead {
+                            name: [name.clone()]
+                                .into_iter()
+                                .chain(keys)
+                                .collect::<Vec<_>>()
+                                .join("_"),
+                            path,
+                            rust_type: "serde_json::Value".to_string(),
+                            at_generation,
+                        });
+                    }
+                }
+            }
+
+            // The other constructors do not read data.
+            _ => {}
         }
+        Ok(())
     }
 }
 
@@ -710,9 +1616,9 @@ mod tests {
 
     #[test]
     fn test() {
-        let package_1: Package = serde_json::from_str(&PACKAGE_JSON).expect("wrong format.");
+        let package_1: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
         let mut pm_1 = PackageManager::new();
-        pm_1.add_package(package_1);
+        pm_1.add_package(package_1).expect("package not added.");
         let t_1 = pm_1.get_type("my_crate::MyType").expect("msg");
         let c_1 = t_1.constructors.get("FromCode").expect("");
         let mut type_params_1 = HashMap::new();
@@ -732,4 +1638,298 @@ mod tests {
                 .expect("")
         );
     }
+
+    #[test]
+    fn emit_structural_args_test() {
+        let pm = PackageManager::new();
+        let mut constructor: Constructor = serde_json::from_str(
+            r#"{"NewWithArbitraryArgs": {
+                "function_name": null,
+                "arguments": [
+                    {"type": "(u8, T)", "name": "pair", "passing": "Move", "construction": {"Constructor": "Default"}},
+                    {"type": "[f32; 4]", "name": "weights", "passing": "Reference", "construction": {"Constructor": "Json"}}
+                ]
+            }}"#,
+        )
+        .expect("wrong format.");
+        if let Constructor::NewWithArbitraryArgs { arguments, .. } = &mut constructor {
+            arguments[0].arg_type.mark_generics(&["T"]);
+        }
+
+        let mut type_params = HashMap::new();
+        type_params.insert("T".to_string(), "u16".to_string());
+        let obj = ObjectDescription {
+            type_name: "my_crate::Filter".to_string(),
+            type_parameter_part: "".to_string(),
+            name: "filter".to_string(),
+            is_mutable: false,
+        };
+
+        let code = constructor
+            .emit_code_template(&obj, &type_params, &pm, &Namespace::new())
+            .expect("emission failed.");
+        assert_eq!(
+            "let filter_pair:(u8, u16) = Default::default();\n\
+             let filter_weights: [f32; 4] = serde_json::from_value(data[\"filter\"][\"weights\"].clone()).expect(\"Could not create 'filter_weights' from Json.\");\n\
+             let filter = my_crate::Filter::new(filter_pair, &filter_weights);",
+            code
+        );
+    }
+
+    #[test]
+    fn argument_constraints_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(
+            r#"{
+            "name": "c",
+            "version": "1.0.0",
+            "crates": {"c": {
+                "types": {
+                    "Wrapper": {
+                        "inputs": null,
+                        "outputs": null,
+                        "type_parameters": [{"name": "T", "where": ["Copy"]}],
+                        "constructors": {"New": {"New": {}}}
+                    },
+                    "Outer": {
+                        "inputs": null,
+                        "outputs": null,
+                        "type_parameters": [{"name": "T", "where": []}],
+                        "constructors": {
+                            "Fixed": {"NewWithArbitraryArgs": {"function_name": null, "arguments": [
+                                {"type": "c::Wrapper<u8>", "name": "inner", "passing": "Move", "construction": {"Constructor": "New"}}
+                            ]}},
+                            "Bound": {"NewWithArbitraryArgs": {"function_name": null, "arguments": [
+                                {"type": "c::Wrapper<T>", "name": "inner", "passing": "Move", "construction": {"Constructor": "New"}}
+                            ]}}
+                        }
+                    }
+                },
+                "modules": {}
+            }}
+        }"#,
+        )
+        .expect("wrong format.");
+        pm.add_package(package).unwrap();
+
+        let outer = pm.get_type("c::Outer").unwrap();
+        let emit = |constructor: &str, binding: &str| {
+            let type_params = HashMap::from([("T".to_string(), binding.to_string())]);
+            let obj = outer.object_description("c::Outer", "outer", &type_params)?;
+            outer.constructors[constructor].emit_code_template(
+                &obj,
+                &type_params,
+                &pm,
+                &Namespace::new(),
+            )
+        };
+
+        // The argument's `where` clauses are checked with its own type arguments.
+        assert!(emit("Fixed", "String").is_ok());
+        assert!(emit("Bound", "u8").is_ok());
+        let error = emit("Bound", "String").unwrap_err().to_string();
+        assert!(error.contains("'String'"), "{}", error);
+    }
+
+    #[test]
+    fn check_data_kind_test() {
+        let pm = PackageManager::new();
+        let fits = |type_name: &str, value: Value| {
+            let td = TypeDescription::from_str(type_name).unwrap();
+            check_data_kind(&td, &value, &pm).is_none()
+        };
+
+        assert!(fits("i64", serde_json::json!(i64::MAX)));
+        assert!(fits("i64", serde_json::json!(i64::MIN)));
+        assert!(!fits("i64", serde_json::json!(u64::MAX)));
+        assert!(!fits("isize", serde_json::json!(u64::MAX)));
+        assert!(!fits("i32", serde_json::json!(u64::MAX)));
+        assert!(fits("i32", serde_json::json!(i32::MIN)));
+        assert!(!fits("i32", serde_json::json!(i32::MAX as i64 + 1)));
+        assert!(fits("i16", serde_json::json!(i16::MIN)));
+        assert!(!fits("i16", serde_json::json!(i16::MIN as i64 - 1)));
+        assert!(fits("u8", serde_json::json!(255)));
+        assert!(!fits("u8", serde_json::json!(256)));
+        assert!(!fits("u8", serde_json::json!(-1)));
+        assert!(fits("u64", serde_json::json!(u64::MAX)));
+        assert!(!fits("u64", serde_json::json!(-1)));
+        assert!(fits("i128", serde_json::json!(u64::MAX)));
+        assert!(!fits("u16", serde_json::json!(1.5)));
+    }
+
+    #[test]
+    fn validate_data_test() {
+        let pm = PackageManager::new();
+        let constructor: Constructor = serde_json::from_str(
+            r#"{"NewWithArbitraryArgs": {
+                "function_name": null,
+                "arguments": [
+                    {"type": "u8", "name": "level", "passing": "Move", "construction": {"Constructor": "Json"}},
+                    {"type": "(bool, char)", "name": "flags", "passing": "Move", "construction": {"Constructor": "Json"}},
+                    {"type": "[f32; 2]", "name": "weights", "passing": "Move", "construction": {"Constructor": "Json"}},
+                    {"type": "i32", "name": "offset", "passing": "Move", "construction": {"Constructor": "Default"}}
+                ]
+            }}"#,
+        )
+        .expect("wrong format.");
+        let obj = ObjectDescription {
+            type_name: "my_crate::Filter".to_string(),
+            type_parameter_part: "".to_string(),
+            name: "filter".to_string(),
+            is_mutable: false,
+        };
+        let validate = |data: Value| {
+            constructor
+                .validate_data(&obj, &HashMap::new(), &pm, &Namespace::new(), &data)
+                .expect("validation failed.")
+        };
+
+        assert!(validate(
+            serde_json::json!({"filter": {"level": 3, "flags": [true, "x"], "weights": [0.5, 1]}})
+        )
+        .is_empty());
+
+        let issues = validate(serde_json::json!({
+            "filter": {"level": 300, "flags": [true, "xy"], "offset": 1},
+            "other": {}
+        }));
+        let summary: Vec<(&str, &DataIssueKind)> =
+            issues.iter().map(|i| (i.name.as_str(), &i.kind)).collect();
+        assert_eq!(
+            vec![
+                (
+                    "filter_level",
+                    &DataIssueKind::WrongKind {
+                        expected: "u8".to_string(),
+                        found: "the number 300".to_string()
+                    }
+                ),
+                (
+                    "filter_flags",
+                    &DataIssueKind::WrongKind {
+                        expected: "(bool, char)".to_string(),
+                        found: "an array of length 2".to_string()
+                    }
+                ),
+                ("filter_weights", &DataIssueKind::Missing),
+                ("filter_offset", &DataIssueKind::Unused),
+                ("other", &DataIssueKind::Unused),
+            ],
+            summary
+        );
+        assert_eq!(
+            "'filter_weights': data[\"filter\"][\"weights\"] is missing.",
+            issues[2].to_string()
+        );
+    }
+
+    #[test]
+    fn namespace_test() {
+        let root = Namespace::new();
+        let mut a = root.clone();
+        a.add_part("a");
+        let mut a_b = root.clone();
+        a_b.add_part("a_b");
+
+        assert_eq!("a_b_c", a.qualified_name("b_c"));
+        assert_eq!("a_b_c_2", a_b.qualified_name("c"));
+        assert_eq!("a_b_c", a.qualified_name("b_c"));
+        assert_eq!(
+            Some(vec!["a_b".to_string(), "c".to_string()]),
+            root.path_of("a_b_c_2")
+        );
+
+        assert_eq!("r#type", root.qualified_name("type"));
+        assert_eq!(Some(vec!["type".to_string()]), root.path_of("r#type"));
+        assert_eq!("self_", root.qualified_name("self"));
+        assert_eq!("my_node_1", root.qualified_name("my-node.1"));
+        assert_eq!("_3d", root.qualified_name("3d"));
+        assert_eq!("data_2", root.qualified_name("data"));
+        assert_eq!("change_observer_2", root.qualified_name("change_observer"));
+
+        // Data keys are not identifiers and are emitted as string literals.
+        let pm = PackageManager::new();
+        let mut ns = Namespace::new();
+        ns.add_part("a\\b");
+        let obj = ObjectDescription {
+            type_name: "i32".to_string(),
+            type_parameter_part: "".to_string(),
+            name: "say \"hi\"".to_string(),
+            is_mutable: false,
+        };
+        let code = Constructor::FromJson
+            .emit_code_template(&obj, &HashMap::new(), &pm, &ns)
+            .unwrap();
+        assert!(code.contains(r#"data["a\\b"]["say \"hi\""].clone()"#));
+    }
+
+    #[test]
+    fn context_object_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(
+            r#"{
+                "name": "metrics",
+                "version": "1.0.0",
+                "crates": {},
+                "context_objects": {
+                    "metrics": {"type": "metrics::Registry", "passing": "Reference"}
+                }
+            }"#,
+        )
+        .expect("wrong format.");
+        pm.add_package(package).unwrap();
+
+        let constructor = |arg_type: &str| -> Constructor {
+            serde_json::from_str(&format!(
+                r#"{{"NewWithArbitraryArgs": {{
+                    "function_name": "new",
+                    "arguments": [
+                        {{"type": "{}", "name": "metrics", "passing": "Reference", "construction": {{"ExistingObject": []}}}}
+                    ]
+                }}}}"#,
+                arg_type
+            ))
+            .expect("wrong format.")
+        };
+        let obj = ObjectDescription {
+            type_name: "my_crate::Counter".to_string(),
+            type_parameter_part: "".to_string(),
+            name: "metrics".to_string(),
+            is_mutable: false,
+        };
+
+        // The generated object does not shadow the context object it is constructed from.
+        let ns = Namespace::with_context_objects(&pm);
+        let code = constructor("metrics::Registry")
+            .emit_code_template(&obj, &HashMap::new(), &pm, &ns)
+            .expect("emission failed.");
+        assert_eq!("\nlet metrics_2 = my_crate::Counter::new(&metrics);", code);
+
+        assert!(constructor("metrics::Other")
+            .emit_code_template(&obj, &HashMap::new(), &pm, &ns)
+            .is_err());
+
+        let observed: Constructor =
+            serde_json::from_str(r#"{"NewWithObserver": {}}"#).expect("wrong format.");
+        let code = observed
+            .emit_code_template(&obj, &HashMap::new(), &pm, &ns)
+            .expect("emission failed.");
+        assert_eq!(
+            "\nlet metrics_2 = my_crate::Counter::new(change_observer.clone());",
+            code
+        );
+
+        assert!(pm
+            .register_context_object(
+                "metrics",
+                ContextObject {
+                    object_type: Box::new(TypeDescription::Type {
+                        name: "u32".to_string(),
+                        type_parameters: None,
+                    }),
+                    passing: ArgumentPassing::Move,
+                },
+            )
+            .is_err());
+    }
 }
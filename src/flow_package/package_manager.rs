@@ -1,16 +1,188 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
-use crate::package::{Crate, Package, Type};
+use anyhow::Error;
+use semver::{Version, VersionReq};
+
+use crate::package::{
+    ArgumentPassing, Constructor, ContextObject, Crate, Package, Type, TypeDescription,
+};
+use crate::templates::{check_templates, TemplateRegistry};
 
 pub struct PackageManager {
-    pub packages: HashMap<String, Package>,
+    /// All registered packages by name, each with all of its registered versions.
+    pub packages: HashMap<String, BTreeMap<Version, Package>>,
+    /// Context objects declared by the registered packages or registered directly, by name.
+    pub context_objects: HashMap<String, ContextObject>,
+    /// Renders `FromCode` templates, each with the partials of its package.
+    pub templates: TemplateRegistry,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    /// Skip broken package files and collect their errors in the [`LoadReport`].
+    Lenient,
+    /// Abort on the first broken package file.
+    Strict,
+}
+
+#[derive(Debug)]
+pub enum PackageLoadErrorKind {
+    Io(std::io::Error),
+    Deserialize {
+        line: usize,
+        column: usize,
+        source: serde_json::Error,
+    },
+    /// The package could be read but not registered (e.g. invalid or duplicate version).
+    Register(Error),
+}
+
+#[derive(Debug)]
+pub struct PackageLoadError {
+    pub path: PathBuf,
+    pub kind: PackageLoadErrorKind,
+}
+
+impl PackageLoadError {
+    fn new(path: impl Into<PathBuf>, kind: PackageLoadErrorKind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for PackageLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PackageLoadErrorKind::Io(e) => {
+                write!(f, "Failed to read '{}'. Reason: {}", self.path.display(), e)
+            }
+            PackageLoadErrorKind::Deserialize {
+                line,
+                column,
+                source,
+            } => write!(
+                f,
+                "Failed to deserialize package '{}' at line {}, column {}. Reason: {}",
+                self.path.display(),
+                line,
+                column,
+                source
+            ),
+            PackageLoadErrorKind::Register(e) => write!(
+                f,
+                "Failed to register package '{}'. Reason: {}",
+                self.path.display(),
+                e
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PackageLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            PackageLoadErrorKind::Io(e) => Some(e),
+            PackageLoadErrorKind::Deserialize { source, .. } => Some(source),
+            PackageLoadErrorKind::Register(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+/// A type together with the package (version) it was found in.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedType<'a> {
+    pub package: &'a Package,
+    pub type_desc: &'a Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeLookupError {
+    NotFound(String),
+    UnknownPackage {
+        type_name: String,
+        package: String,
+    },
+    /// The name matched types in several packages. Candidates are package-qualified names.
+    Ambiguous {
+        type_name: String,
+        candidates: Vec<String>,
+    },
+}
+
+impl fmt::Display for TypeLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeLookupError::NotFound(type_name) => {
+                write!(f, "Type description for '{}' not found.", type_name)
+            }
+            TypeLookupError::UnknownPackage { type_name, package } => write!(
+                f,
+                "Package '{}' of type '{}' not found.",
+                package, type_name
+            ),
+            TypeLookupError::Ambiguous {
+                type_name,
+                candidates,
+            } => write!(
+                f,
+                "Type '{}' is ambiguous, qualify it with its package. Candidates: {}",
+                type_name,
+                candidates.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TypeLookupError {}
+
+/// The type of all tuples and arrays of primitives.
+fn primitive_structure_type() -> &'static Type {
+    static PRIMITIVE_STRUCTURE: OnceLock<Type> = OnceLock::new();
+    PRIMITIVE_STRUCTURE.get_or_init(Type::new_primitive_type)
+}
+
+/// Splits `my_package/my_crate::Type` into the package name and the Rust path of the type.
+pub fn split_qualified_type_name(type_name: &str) -> (Option<&str>, &str) {
+    match type_name.split_once('/') {
+        Some((package, path)) => (Some(package), path),
+        None => (None, type_name),
+    }
+}
+
+/// Outcome of [`PackageManager::load_from_folder`].
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    /// Names (file stems) of the successfully loaded packages.
+    pub loaded: Vec<String>,
+    pub errors: Vec<PackageLoadError>,
+}
+
+impl LoadReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Default for PackageManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PackageManager {
     pub fn new() -> Self {
         let mut pm = Self {
             packages: HashMap::new(),
+            context_objects: HashMap::new(),
+            templates: TemplateRegistry::new(),
         };
 
         pm.add_built_in_package();
@@ -18,53 +190,99 @@ impl PackageManager {
         pm
     }
 
+    /// Loads all packages of a folder that can be loaded. Use [`PackageManager::load_from_folder`]
+    /// to learn which files failed.
     pub fn new_from_folder(directory_path: &str) -> Self {
-        let mut packages: HashMap<String, Package> = HashMap::new();
-
-        let entries = fs::read_dir(directory_path);
-        match entries {
-            Ok(es) => {
-                for entry in es {
-                    if let Ok(entry) = entry {
-                        if let Some(extension) = entry.path().extension() {
-                            if extension == "json" {
-                                if let Some(file_name) = entry.path().file_stem() {
-                                    let package_name = file_name.to_string_lossy().to_string();
-
-                                    if let Ok(contents) = fs::read_to_string(entry.path()) {
-                                        let package = serde_json::from_str::<Package>(&contents);
-                                        match package {
-                                            Ok(p) => {
-                                                packages.insert(package_name.clone(), p);
-                                            }
-                                            Err(e) => {
-                                                eprintln!(
-                                                    "-> Failed to deserialize package: {}. Reason: {}",
-                                                    package_name,
-                                                    e.to_string()
-                                                );
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+        let Ok((pm, _)) = Self::load_folder(directory_path, |report, error| {
+            report.errors.push(error);
+            Ok::<(), Infallible>(())
+        });
+        pm
+    }
+
+    /// Loads all `*.json` packages of a folder.
+    ///
+    /// In [`LoadMode::Lenient`] every failure is recorded in the returned [`LoadReport`] and
+    /// loading continues with the next file. In [`LoadMode::Strict`] the first failure is returned
+    /// as error. Files are processed in lexical order of their path.
+    pub fn load_from_folder(
+        directory_path: &str,
+        mode: LoadMode,
+    ) -> Result<(Self, LoadReport), PackageLoadError> {
+        Self::load_folder(directory_path, |report, error| match mode {
+            LoadMode::Strict => Err(error),
+            LoadMode::Lenient => {
+                report.errors.push(error);
+                Ok(())
+            }
+        })
+    }
+
+    /// Loads a folder, passing every failure to `fail`, which either records it or aborts.
+    fn load_folder<E>(
+        directory_path: &str,
+        mut fail: impl FnMut(&mut LoadReport, PackageLoadError) -> Result<(), E>,
+    ) -> Result<(Self, LoadReport), E> {
+        let mut pm = Self::new();
+        let mut report = LoadReport::default();
+
+        let mut paths = Vec::new();
+        match fs::read_dir(directory_path) {
+            Ok(entries) => {
+                for entry in entries {
+                    match entry {
+                        Ok(entry) => paths.push(entry.path()),
+                        Err(e) => fail(
+                            &mut report,
+                            PackageLoadError::new(directory_path, PackageLoadErrorKind::Io(e)),
+                        )?,
                     }
                 }
             }
-            Err(e) => {
-                eprintln!(
-                    "-> Failed to read package folder '{}'. Reason: {}",
-                    directory_path,
-                    e.to_string()
-                );
+            Err(e) => fail(
+                &mut report,
+                PackageLoadError::new(directory_path, PackageLoadErrorKind::Io(e)),
+            )?,
+        }
+        paths.sort();
+
+        for path in paths {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(file_name) = path.file_stem() else {
+                continue;
+            };
+            let package_name = file_name.to_string_lossy().to_string();
+
+            let result = Self::load_package_file(&path).and_then(|package| {
+                pm.add_package(package)
+                    .map_err(|e| PackageLoadError::new(&path, PackageLoadErrorKind::Register(e)))
+            });
+            match result {
+                Ok(()) => report.loaded.push(package_name),
+                Err(error) => fail(&mut report, error)?,
             }
         }
 
-        let mut pm = PackageManager { packages: packages };
-        pm.add_built_in_package();
+        Ok((pm, report))
+    }
 
-        pm
+    /// Reads and deserializes a single package file.
+    pub fn load_package_file(path: &Path) -> Result<Package, PackageLoadError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| PackageLoadError::new(path, PackageLoadErrorKind::Io(e)))?;
+
+        serde_json::from_str::<Package>(&contents).map_err(|e| {
+            PackageLoadError::new(
+                path,
+                PackageLoadErrorKind::Deserialize {
+                    line: e.line(),
+                    column: e.column(),
+                    source: e,
+                },
+            )
+        })
     }
 
     fn add_built_in_package(&mut self) {
@@ -74,64 +292,416 @@ impl PackageManager {
             "f32", "f64", "bool", "char",
         ];
 
+        // traits implemented by all primitives.
+        let common_traits = [
+            "Clone",
+            "Copy",
+            "Debug",
+            "Default",
+            "PartialEq",
+            "PartialOrd",
+            "Send",
+            "Sync",
+            "serde::Serialize",
+            "serde::Deserialize",
+        ];
+        let total_order_traits = ["Eq", "Ord", "Hash"];
+        let arithmetic_traits = ["Add", "Sub", "Mul", "Div", "Rem"];
+
         let mut types = HashMap::new();
         for prim in prims {
-            types.insert(prim.to_string(), Type::new_primitive_type());
+            let mut t = Type::new_primitive_type();
+            t.traits.extend(common_traits.map(String::from));
+            t.traits.push("Display".to_string());
+            if !prim.starts_with('f') {
+                t.traits.extend(total_order_traits.map(String::from));
+            }
+            if prim != "bool" && prim != "char" {
+                t.traits.extend(arithmetic_traits.map(String::from));
+            }
+            types.insert(prim.to_string(), t);
         }
 
         // the "no-type".
-        types.insert("()".to_string(), Type::new_simple());
+        let mut unit = Type::new_simple();
+        unit.traits.extend(common_traits.map(String::from));
+        unit.traits.extend(total_order_traits.map(String::from));
+        types.insert("()".to_string(), unit);
 
         let mut crates = HashMap::new();
         crates.insert("primitives".to_string(), Crate::new_with_types(types));
 
-        self.add_package(Package {
-            name: "built-in".to_string(),
-            version: "1.0.0".to_string(),
-            crates: crates,
-        })
+        // The objects flowrs passes to `NewWithObserver` and `NewWithObserverAndContext`.
+        let mut context_objects = HashMap::new();
+        for (name, type_name) in [
+            ("change_observer", "flowrs::node::ChangeObserver"),
+            ("context", "flowrs::node::Context"),
+        ] {
+            context_objects.insert(
+                name.to_string(),
+                ContextObject {
+                    object_type: Box::new(TypeDescription::Type {
+                        name: type_name.to_string(),
+                        type_parameters: None,
+                    }),
+                    passing: ArgumentPassing::Clone,
+                },
+            );
+        }
+
+        // Registered directly: the package has nothing to check and its name is reserved.
+        self.context_objects.extend(context_objects.clone());
+        self.packages
+            .entry("built-in".to_string())
+            .or_default()
+            .insert(
+                Version::new(1, 0, 0),
+                Package {
+                    name: "built-in".to_string(),
+                    version: "1.0.0".to_string(),
+                    crates,
+                    dependencies: HashMap::new(),
+                    context_objects,
+                    partials: HashMap::new(),
+                },
+            );
     }
 
-    pub fn add_package(&mut self, package: Package) {
-        if !self.packages.contains_key(&package.name) {
-            self.packages.insert(package.name.clone(), package);
+    /// Registers a package. Several versions of the same package may coexist, but a
+    /// name/version pair can only be registered once. The name "built-in" is reserved.
+    pub fn add_package(&mut self, mut package: Package) -> Result<(), Error> {
+        if package.name == "built-in" {
+            return Err(Error::msg(
+                "The package name 'built-in' is reserved for the built-in types.",
+            ));
+        }
+        let version = package.semver().map_err(|e| {
+            Error::msg(format!(
+                "Invalid version '{}' of package '{}': {}",
+                package.version, package.name, e
+            ))
+        })?;
+
+        if self
+            .packages
+            .get(&package.name)
+            .is_some_and(|versions| versions.contains_key(&version))
+        {
+            return Err(Error::msg(format!(
+                "Package '{}' in version '{}' is already registered.",
+                package.name, version
+            )));
+        }
+        for (name, context_object) in &package.context_objects {
+            self.check_context_object(name, context_object)?;
+        }
+        let issues = check_templates(&package);
+        if !issues.is_empty() {
+            return Err(Error::msg(format!(
+                "Invalid templates in package '{}':\n{}",
+                package.name,
+                issues
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            )));
+        }
+        self.templates.add_partials(&package)?;
+        package.set_template_package();
+
+        for (name, context_object) in &package.context_objects {
+            self.context_objects
+                .insert(name.clone(), context_object.clone());
         }
+        self.packages
+            .entry(package.name.clone())
+            .or_default()
+            .insert(version, package);
+
+        Ok(())
+    }
+
+    /// Registers an object that exists where flows are constructed, so constructors can receive
+    /// it. Registering a name again is only allowed with the same declaration.
+    pub fn register_context_object(
+        &mut self,
+        name: &str,
+        context_object: ContextObject,
+    ) -> Result<(), Error> {
+        self.check_context_object(name, &context_object)?;
+        self.context_objects
+            .insert(name.to_string(), context_object);
+        Ok(())
+    }
+
+    /// The registered package declaring a `FromCode` constructor, e.g. to render its template
+    /// with the partials of the package.
+    pub fn package_of(&self, constructor: &Constructor) -> Option<&Package> {
+        let Constructor::FromCode {
+            package: Some((name, version)),
+            ..
+        } = constructor
+        else {
+            return None;
+        };
+        self.get_package_version(name, &Version::parse(version).ok()?)
     }
 
+    /// The registered context object `name`.
+    pub fn context_object(&self, name: &str) -> Result<&ContextObject, Error> {
+        self.context_objects
+            .get(name)
+            .ok_or_else(|| Error::msg(format!("Context object '{}' is not registered.", name)))
+    }
+
+    fn check_context_object(
+        &self,
+        name: &str,
+        context_object: &ContextObject,
+    ) -> Result<(), Error> {
+        match self.context_objects.get(name) {
+            Some(registered) if registered != context_object => Err(Error::msg(format!(
+                "Context object '{}' is already registered with type '{}'.",
+                name, registered.object_type
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns all registered versions of all packages.
     pub fn get_all_packages(&self) -> Vec<Package> {
-        self.packages.values().cloned().collect()
+        self.packages
+            .values()
+            .flat_map(|versions| versions.values())
+            .cloned()
+            .collect()
     }
 
+    /// Returns the newest registered version of a package.
     pub fn get_package(&self, package_name: &str) -> Option<&Package> {
-        self.packages.get(package_name)
+        self.packages
+            .get(package_name)
+            .and_then(|versions| versions.values().next_back())
     }
 
+    /// Whether a type name names a type of the built-in package, e.g. `u8` or `()`.
+    pub fn is_built_in_type(&self, type_name: &str) -> bool {
+        self.get_package("built-in")
+            .and_then(|p| p.crates.get("primitives"))
+            .is_some_and(|c| c.types.contains_key(type_name))
+    }
+
+    /// Returns exactly the given version of a package.
+    pub fn get_package_version(&self, package_name: &str, version: &Version) -> Option<&Package> {
+        self.packages
+            .get(package_name)
+            .and_then(|versions| versions.get(version))
+    }
+
+    /// Returns the newest version of a package that satisfies the requirement, e.g. `^1.2`.
+    pub fn get_package_matching(
+        &self,
+        package_name: &str,
+        requirement: &VersionReq,
+    ) -> Option<&Package> {
+        self.get_package_versions_matching(package_name, requirement)
+            .into_iter()
+            .next()
+    }
+
+    /// Returns all versions of a package that satisfy the requirement, newest first.
+    pub fn get_package_versions_matching(
+        &self,
+        package_name: &str,
+        requirement: &VersionReq,
+    ) -> Vec<&Package> {
+        self.packages
+            .get(package_name)
+            .into_iter()
+            .flat_map(|versions| versions.iter().rev())
+            .filter(|(version, _)| requirement.matches(version))
+            .map(|(_, package)| package)
+            .collect()
+    }
+
+    /// Looks up a type in the newest versions of all packages.
+    ///
+    /// See [`PackageManager::resolve_type`] for the accepted names. Ambiguous names yield `None`.
     pub fn get_type(&self, type_name: &str) -> Option<&Type> {
-        let type_ids: Vec<&str> = type_name.split("::").collect();
+        self.resolve_type(type_name).ok().map(|r| r.type_desc)
+    }
+
+    /// Looks up a type, restricting the package versions that are considered.
+    ///
+    /// `requirements` maps package names to version requirements. Packages without a
+    /// requirement are matched with any version. Within a package, only the newest compatible
+    /// version is searched, so one flow never mixes types of several versions.
+    pub fn get_type_matching(
+        &self,
+        type_name: &str,
+        requirements: &HashMap<String, VersionReq>,
+    ) -> Option<&Type> {
+        self.resolve_type_matching(type_name, requirements, None)
+            .ok()
+            .map(|r| r.type_desc)
+    }
+
+    /// Resolves a type name to the package that defines it.
+    ///
+    /// Type names are either unqualified (`my_crate::module::Type`, or `i32` for built-in types)
+    /// or qualified by package (`my_package/my_crate::module::Type`).
+    pub fn resolve_type(&self, type_name: &str) -> Result<ResolvedType<'_>, TypeLookupError> {
+        self.resolve_type_matching(type_name, &HashMap::new(), None)
+    }
+
+    /// Resolves a type name with the following precedence:
+    ///
+    /// 1. a package-qualified name is only looked up in that package,
+    /// 2. single-part names are looked up in the built-in package,
+    /// 3. the `context_package` (the package the name is used in) itself,
+    /// 4. the direct dependencies of the `context_package`,
+    /// 5. all registered packages.
+    ///
+    /// The first step that yields a match wins. If a step yields matches in more than one
+    /// package, a [`TypeLookupError::Ambiguous`] listing all candidates is returned. The
+    /// dependency requirements of the `context_package` select the versions of its dependencies
+    /// unless `requirements` names them.
+    pub fn resolve_type_matching(
+        &self,
+        type_name: &str,
+        requirements: &HashMap<String, VersionReq>,
+        context_package: Option<&str>,
+    ) -> Result<ResolvedType<'_>, TypeLookupError> {
+        let (package_name, path) = split_qualified_type_name(type_name);
+        let type_ids: Vec<&str> = path.split("::").collect();
+
+        if let Some(package_name) = package_name {
+            if !self.packages.contains_key(package_name) {
+                return Err(TypeLookupError::UnknownPackage {
+                    type_name: type_name.to_string(),
+                    package: package_name.to_string(),
+                });
+            }
+            return self
+                .find_in_packages(&type_ids, &[package_name], requirements)
+                .pop()
+                .ok_or_else(|| TypeLookupError::NotFound(type_name.to_string()));
+        }
+
+        let not_found = || TypeLookupError::NotFound(type_name.to_string());
+        let built_in = self.get_package("built-in").ok_or_else(not_found)?;
+
+        // Tuples and arrays are not registered anywhere, but are known if all their elements are
+        // primitives. They can be created like primitives.
+        if type_name.starts_with(['(', '[']) {
+            if let Ok(td @ (TypeDescription::Tuple { .. } | TypeDescription::Array { .. })) =
+                TypeDescription::from_str(type_name)
+            {
+                return if self.is_primitive_structure(&td) {
+                    Ok(ResolvedType {
+                        package: built_in,
+                        type_desc: primitive_structure_type(),
+                    })
+                } else {
+                    Err(TypeLookupError::NotFound(type_name.to_string()))
+                };
+            }
+        }
 
         // check built-in types.
         if type_ids.len() == 1 {
-            return self
-                .packages
-                .get("built-in")
-                .expect("built-in package not available.")
+            return built_in
                 .crates
                 .get("primitives")
-                .expect("primitives crate not available.")
-                .types
-                .get(type_ids[0]);
+                .and_then(|primitives| primitives.types.get(type_ids[0]))
+                .map(|type_desc| ResolvedType {
+                    package: built_in,
+                    type_desc,
+                })
+                .ok_or_else(not_found);
+        }
+
+        let mut requirements = requirements.clone();
+        let mut scopes: Vec<Vec<&str>> = Vec::new();
+        if let Some(context) = context_package.and_then(|c| self.get_package(c)) {
+            // Invalid requirements are reported by `check_dependencies`.
+            for (dependency, requirement) in &context.dependencies {
+                if let Ok(requirement) = VersionReq::parse(requirement) {
+                    requirements
+                        .entry(dependency.clone())
+                        .or_insert(requirement);
+                }
+            }
+            scopes.push(vec![context.name.as_str()]);
+            let mut dependencies: Vec<&str> =
+                context.dependencies.keys().map(|d| d.as_str()).collect();
+            dependencies.sort();
+            scopes.push(dependencies);
         }
+        let mut all: Vec<&str> = self.packages.keys().map(|p| p.as_str()).collect();
+        all.sort();
+        scopes.push(all);
 
-        // iterate over packages and return type if available.
-        // Note: We cannot handle same crate, same type, different package situations.
-        for (_, p) in &self.packages {
-            let res = self.get_type_from_package(&type_ids, p);
-            if res.is_some() {
-                return res;
+        for scope in scopes {
+            let mut found = self.find_in_packages(&type_ids, &scope, &requirements);
+            match found.len() {
+                0 => continue,
+                1 => return Ok(found.pop().unwrap()),
+                _ => {
+                    return Err(TypeLookupError::Ambiguous {
+                        type_name: type_name.to_string(),
+                        candidates: found
+                            .iter()
+                            .map(|r| format!("{}/{}", r.package.name, path))
+                            .collect(),
+                    })
+                }
             }
         }
 
-        Option::None
+        Err(TypeLookupError::NotFound(type_name.to_string()))
+    }
+
+    /// Whether the description is a built-in primitive or a tuple/array of those.
+    fn is_primitive_structure(&self, td: &TypeDescription) -> bool {
+        match td {
+            TypeDescription::Tuple { elements } => {
+                elements.iter().all(|e| self.is_primitive_structure(e))
+            }
+            TypeDescription::Array { element, .. } => self.is_primitive_structure(element),
+            TypeDescription::Type {
+                name,
+                type_parameters: None,
+            } => !name.contains("::") && !name.contains('/') && self.get_type(name).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Returns at most one match per package, looked up in its newest compatible version only.
+    fn find_in_packages(
+        &self,
+        type_ids: &Vec<&str>,
+        package_names: &[&str],
+        requirements: &HashMap<String, VersionReq>,
+    ) -> Vec<ResolvedType<'_>> {
+        let mut found = Vec::new();
+        for package_name in package_names {
+            let requirement = requirements
+                .get(*package_name)
+                .cloned()
+                .unwrap_or(VersionReq::STAR);
+            let Some(p) = self.get_package_matching(package_name, &requirement) else {
+                continue;
+            };
+            if let Some(type_desc) = self.get_type_from_package(type_ids, p) {
+                found.push(ResolvedType {
+                    package: p,
+                    type_desc,
+                });
+            }
+        }
+        found
     }
 
     pub fn get_type_from_package<'a>(
@@ -182,11 +752,217 @@ mod tests {
         let pm = PackageManager::new();
         let package_opt = pm.get_package("built-in");
 
-        assert!(!package_opt.is_none());
+        assert!(package_opt.is_some());
         let package = package_opt.unwrap();
         assert_eq!("built-in", package.name);
     }
 
+    fn write_package_folder(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("flowrs_package_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file_name, contents) in files {
+            fs::write(dir.join(file_name), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn load_from_folder_test() {
+        let dir = write_package_folder(
+            "load",
+            &[
+                (
+                    "good.json",
+                    r#"{"name": "good", "version": "1.0.0", "crates": {}}"#,
+                ),
+                ("bad.json", "{\n  \"name\": \"bad\",\n  \"version\": 1\n}"),
+                ("ignored.txt", "not a package"),
+            ],
+        );
+        let dir_str = dir.to_str().unwrap();
+
+        let (pm, report) = PackageManager::load_from_folder(dir_str, LoadMode::Lenient).unwrap();
+        assert_eq!(vec!["good".to_string()], report.loaded);
+        assert_eq!(1, report.errors.len());
+        assert!(pm.get_package("good").is_some());
+        assert!(pm.get_package("built-in").is_some());
+
+        let pm = PackageManager::new_from_folder(dir_str);
+        assert!(pm.get_package("good").is_some());
+        assert!(pm.get_package("bad").is_none());
+
+        let error = &report.errors[0];
+        assert_eq!(dir.join("bad.json"), error.path);
+        assert!(matches!(
+            error.kind,
+            PackageLoadErrorKind::Deserialize { line: 3, .. }
+        ));
+
+        let strict = PackageManager::load_from_folder(dir_str, LoadMode::Strict);
+        assert_eq!(dir.join("bad.json"), strict.err().unwrap().path);
+
+        let missing = PackageManager::load_from_folder("/does/not/exist", LoadMode::Lenient);
+        let (_, report) = missing.unwrap();
+        assert!(matches!(report.errors[0].kind, PackageLoadErrorKind::Io(_)));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reserved_package_name_test() {
+        let dir = write_package_folder(
+            "reserved",
+            &[(
+                "built-in.json",
+                r#"{"name": "built-in", "version": "9.0.0", "crates": {}}"#,
+            )],
+        );
+
+        let (pm, report) =
+            PackageManager::load_from_folder(dir.to_str().unwrap(), LoadMode::Lenient).unwrap();
+        assert!(report.loaded.is_empty());
+        assert!(matches!(
+            report.errors[0].kind,
+            PackageLoadErrorKind::Register(_)
+        ));
+        assert_eq!("1.0.0", pm.get_package("built-in").unwrap().version);
+        assert!(pm.resolve_type("i32").is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn versioned_package(version: &str, type_name: &str) -> Package {
+        let mut types = HashMap::new();
+        types.insert(type_name.to_string(), Type::new_simple());
+        let mut crates = HashMap::new();
+        crates.insert("my_crate".to_string(), Crate::new_with_types(types));
+        Package {
+            name: "my_package".to_string(),
+            version: version.to_string(),
+            crates,
+            dependencies: HashMap::new(),
+            context_objects: HashMap::new(),
+            partials: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn package_versions_test() {
+        let mut pm = PackageManager::new();
+        pm.add_package(versioned_package("1.2.0", "Old")).unwrap();
+        pm.add_package(versioned_package("1.10.1", "Old")).unwrap();
+        pm.add_package(versioned_package("2.0.0", "New")).unwrap();
+        assert!(pm.add_package(versioned_package("2.0.0", "New")).is_err());
+        assert!(pm.add_package(versioned_package("two", "New")).is_err());
+
+        assert_eq!("2.0.0", pm.get_package("my_package").unwrap().version);
+        let req = VersionReq::parse("^1.2").unwrap();
+        assert_eq!(
+            "1.10.1",
+            pm.get_package_matching("my_package", &req).unwrap().version
+        );
+        assert_eq!(
+            2,
+            pm.get_package_versions_matching("my_package", &req).len()
+        );
+
+        let mut requirements = HashMap::new();
+        requirements.insert("my_package".to_string(), req);
+        assert!(pm.get_type("my_crate::New").is_some());
+        // Only the newest version is searched, older versions are selected by requirement.
+        assert!(pm.get_type("my_crate::Old").is_none());
+        assert!(pm
+            .get_type_matching("my_crate::New", &requirements)
+            .is_none());
+        assert!(pm
+            .get_type_matching("my_crate::Old", &requirements)
+            .is_some());
+    }
+
+    #[test]
+    fn resolve_type_test() {
+        let mut pm = PackageManager::new();
+        let mut other = versioned_package("1.0.0", "Old");
+        other.name = "other_package".to_string();
+        other
+            .dependencies
+            .insert("my_package".to_string(), "^1".to_string());
+        pm.add_package(other).unwrap();
+        pm.add_package(versioned_package("1.0.0", "Old")).unwrap();
+        pm.add_package(versioned_package("2.0.0", "New")).unwrap();
+        let mut user = versioned_package("1.0.0", "User");
+        user.name = "user_package".to_string();
+        user.crates = HashMap::new();
+        user.dependencies
+            .insert("my_package".to_string(), "^1".to_string());
+        pm.add_package(user).unwrap();
+
+        let version_1 =
+            HashMap::from([("my_package".to_string(), VersionReq::parse("^1").unwrap())]);
+        assert_eq!(
+            Err(TypeLookupError::Ambiguous {
+                type_name: "my_crate::Old".to_string(),
+                candidates: vec![
+                    "my_package/my_crate::Old".to_string(),
+                    "other_package/my_crate::Old".to_string()
+                ]
+            }),
+            pm.resolve_type_matching("my_crate::Old", &version_1, None)
+                .map(|r| r.package.name.clone())
+        );
+
+        // The newest version of my_package does not define Old anymore and older versions are
+        // not searched.
+        assert_eq!(
+            "other_package",
+            pm.resolve_type("my_crate::Old").unwrap().package.name
+        );
+        assert_eq!(
+            Err(TypeLookupError::NotFound(
+                "my_package/my_crate::Old".to_string()
+            )),
+            pm.resolve_type("my_package/my_crate::Old")
+                .map(|r| r.package.name.clone())
+        );
+        let resolved = pm
+            .resolve_type_matching("my_package/my_crate::Old", &version_1, None)
+            .unwrap();
+        assert_eq!("1.0.0", resolved.package.version);
+        let resolved = pm
+            .resolve_type_matching("my_crate::Old", &HashMap::new(), Some("user_package"))
+            .unwrap();
+        assert_eq!(
+            ("my_package", "1.0.0"),
+            (
+                resolved.package.name.as_str(),
+                resolved.package.version.as_str()
+            )
+        );
+        let resolved = pm.resolve_type("my_crate::New").unwrap();
+        assert_eq!("my_package", resolved.package.name);
+        let resolved = pm
+            .resolve_type_matching("my_crate::Old", &HashMap::new(), Some("other_package"))
+            .unwrap();
+        assert_eq!("other_package", resolved.package.name);
+        assert_eq!("built-in", pm.resolve_type("i32").unwrap().package.name);
+        assert!(matches!(
+            pm.resolve_type("nope/my_crate::Old"),
+            Err(TypeLookupError::UnknownPackage { .. })
+        ));
+    }
+
+    #[test]
+    fn get_structural_type_test() {
+        let pm = PackageManager::new();
+        assert!(pm.get_type("(u8, [f32; 4])").is_some());
+        assert!(pm.get_type("[(bool, char); 2]").is_some());
+        assert!(pm.get_type("(u8, String)").is_none());
+        assert!(pm.get_type("[u8]").is_none());
+        assert!(pm.get_type("()").unwrap().constructors.is_empty());
+    }
+
     #[test]
     fn get_all_packages_test() {
         let pm = PackageManager::new();
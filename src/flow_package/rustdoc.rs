@@ -0,0 +1,673 @@
+use anyhow::Error;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::extract::{
+    function_constructor, trait_constructor, ExtractOptions, ExtractWarning, Extraction,
+};
+use crate::package::{Crate, Input, Module, Output, Type, TypeDescription, TypeParameter};
+
+/// Builds the package crate of the node structs documented in the output of
+/// `rustdoc --output-format json`, tested with format version 57.
+///
+/// Types are placed at every public path they can be used with, including re-exports such as
+/// `pub use private_module::Node`, which is where [`crate::extract::extract_crate`] cannot look.
+/// Nodes, ports and constructors are recognized like there.
+pub fn import_rustdoc(json: &str, options: &ExtractOptions) -> Result<Extraction, Error> {
+    let document: Value = serde_json::from_str(json)
+        .map_err(|e| Error::msg(format!("Invalid rustdoc JSON: {}", e)))?;
+    let root = id_key(&document["root"])
+        .ok_or_else(|| Error::msg("Invalid rustdoc JSON: missing 'root'."))?;
+    let index = document["index"]
+        .as_object()
+        .ok_or_else(|| Error::msg("Invalid rustdoc JSON: missing 'index'."))?;
+    let crate_name = index
+        .get(&root)
+        .and_then(|item| item["name"].as_str())
+        .ok_or_else(|| Error::msg("Invalid rustdoc JSON: the root module has no name."))?
+        .to_string();
+
+    let mut importer = Importer {
+        index,
+        options,
+        public_paths: HashMap::new(),
+        structs: Vec::new(),
+        visited: HashSet::new(),
+        walking: Vec::new(),
+        warnings: Vec::new(),
+    };
+    importer.module(&root, &crate_name);
+
+    let mut krate = Crate {
+        types: HashMap::new(),
+        modules: HashMap::new(),
+        dependency: None,
+    };
+    for (id, path) in importer.structs.clone() {
+        if let Some(type_desc) = importer.node(&id, &path) {
+            insert_type(&mut krate, &path, type_desc);
+        }
+    }
+
+    let mut warnings = importer.warnings;
+    warnings.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(Extraction {
+        crate_name,
+        krate,
+        warnings,
+    })
+}
+
+/// Ids are numbers in recent format versions and strings in older ones.
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Places a type at its path, e.g. `my_crate::module::Node`, creating the modules on the way.
+fn insert_type(krate: &mut Crate, path: &str, type_desc: Type) {
+    let mut segments: Vec<&str> = path.split("::").skip(1).collect();
+    let Some(name) = segments.pop() else {
+        return;
+    };
+    let (mut types, mut modules) = (&mut krate.types, &mut krate.modules);
+    for segment in segments {
+        let module = modules
+            .entry(segment.to_string())
+            .or_insert_with(|| Module {
+                types: HashMap::new(),
+                modules: HashMap::new(),
+            });
+        (types, modules) = (&mut module.types, &mut module.modules);
+    }
+    types.insert(name.to_string(), type_desc);
+}
+
+struct Importer<'a> {
+    index: &'a serde_json::Map<String, Value>,
+    options: &'a ExtractOptions,
+    /// The first public path of every item, used to name the types of ports.
+    public_paths: HashMap<String, String>,
+    /// Public structs with each path they can be used with.
+    structs: Vec<(String, String)>,
+    /// Modules with the path they were walked at.
+    visited: HashSet<(String, String)>,
+    /// The modules being walked. Re-exports can form cycles, e.g. `pub use crate::*` in a
+    /// `prelude` module, whose paths grow with every round.
+    walking: Vec<String>,
+    warnings: Vec<ExtractWarning>,
+}
+
+impl Importer<'_> {
+    fn warn(&mut self, path: &str, message: String) {
+        self.warnings.push(ExtractWarning {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn item(&self, id: &str) -> Option<&Value> {
+        self.index.get(id)
+    }
+
+    /// Walks the public items of a module and the items it re-exports.
+    fn module(&mut self, id: &str, path: &str) {
+        if self.walking.iter().any(|walking| walking == id)
+            || !self.visited.insert((id.to_string(), path.to_string()))
+        {
+            return;
+        }
+        let Some(items) = self
+            .item(id)
+            .and_then(|module| module["inner"]["module"]["items"].as_array())
+            .cloned()
+        else {
+            return;
+        };
+
+        self.walking.push(id.to_string());
+        self.module_items(&items, path);
+        self.walking.pop();
+    }
+
+    fn module_items(&mut self, items: &[Value], path: &str) {
+        for item_id in items.iter().filter_map(id_key) {
+            let Some(item) = self.item(&item_id).cloned() else {
+                continue;
+            };
+            if item["visibility"] != "public" {
+                continue;
+            }
+            let inner = &item["inner"];
+            if let Some(re_export) = inner.get("use") {
+                // Re-exports of items that are not documented, e.g. of other crates, have no id.
+                let Some(target) = id_key(&re_export["id"]) else {
+                    continue;
+                };
+                let name = re_export["name"].as_str().unwrap_or_default();
+                if re_export["is_glob"] == true {
+                    self.module(&target, path);
+                } else {
+                    self.named_item(&target, &format!("{}::{}", path, name));
+                }
+            } else if let Some(name) = item["name"].as_str() {
+                self.named_item(&item_id, &format!("{}::{}", path, name));
+            }
+        }
+    }
+
+    fn named_item(&mut self, id: &str, path: &str) {
+        let Some(inner) = self.item(id).map(|item| &item["inner"]) else {
+            return;
+        };
+        let is_module = inner.get("module").is_some();
+        let is_struct = inner.get("struct").is_some();
+        self.public_paths
+            .entry(id.to_string())
+            .or_insert_with(|| path.to_string());
+        if is_module {
+            self.module(id, path);
+        } else if is_struct {
+            self.structs.push((id.to_string(), path.to_string()));
+        }
+    }
+
+    /// Describes a struct at a path, `None` if it is no node.
+    fn node(&mut self, id: &str, path: &str) -> Option<Type> {
+        let item = self.item(id)?.clone();
+        let definition = &item["inner"]["struct"];
+        let fields: Vec<Value> = definition["kind"]["plain"]["fields"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(id_key)
+            .filter_map(|field_id| self.item(&field_id).cloned())
+            .collect();
+        let ports: Vec<(&Value, bool)> = fields
+            .iter()
+            .filter_map(|field| self.port(field).map(|is_input| (field, is_input)))
+            .collect();
+        if ports.is_empty() && !has_attribute(&item, &self.options.node_attribute) {
+            return None;
+        }
+
+        let type_parameters = self.type_parameters(path, &definition["generics"]);
+        let mut inputs = HashMap::new();
+        let mut outputs = HashMap::new();
+        for (field, is_input) in ports {
+            let name = field["name"].as_str().unwrap_or_default().to_string();
+            let Some(port_type) = self.port_type(path, &name, field, &type_parameters) else {
+                continue;
+            };
+            if is_input {
+                inputs.insert(
+                    name,
+                    Input {
+                        input_type: port_type,
+                    },
+                );
+            } else {
+                outputs.insert(
+                    name,
+                    Output {
+                        output_type: port_type,
+                    },
+                );
+            }
+        }
+
+        let mut constructors = HashMap::new();
+        let mut traits = Vec::new();
+        let impls: Vec<String> = definition["impls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(id_key)
+            .collect();
+        for impl_id in impls {
+            let Some(implementation) = self.item(&impl_id).map(|i| i["inner"]["impl"].clone())
+            else {
+                continue;
+            };
+            // Auto traits and blanket implementations are not written for the node.
+            if implementation["is_synthetic"] == true || !implementation["blanket_impl"].is_null() {
+                continue;
+            }
+            if implementation["trait"].is_null() {
+                self.inherent_constructors(path, id, &implementation, &mut constructors);
+            } else {
+                let trait_name = self.render_path(&implementation["trait"]).ok()?;
+                constructors.extend(trait_constructor(&trait_name));
+                traits.push(trait_name);
+            }
+        }
+        traits.sort();
+        traits.dedup();
+
+        Some(Type {
+            inputs: (!inputs.is_empty()).then_some(inputs),
+            outputs: (!outputs.is_empty()).then_some(outputs),
+            type_parameters: (!type_parameters.is_empty()).then_some(type_parameters),
+            constructors,
+            traits,
+        })
+    }
+
+    /// Whether a field is an input (`Some(true)`) or an output (`Some(false)`) port.
+    fn port(&self, field: &Value) -> Option<bool> {
+        if has_attribute(field, "input") {
+            return Some(true);
+        }
+        if has_attribute(field, "output") {
+            return Some(false);
+        }
+        let field_path = field["inner"]["struct_field"]["resolved_path"]["path"].as_str()?;
+        let name = field_path.rsplit("::").next()?;
+        if name == self.options.input_type {
+            Some(true)
+        } else if name == self.options.output_type {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn port_type(
+        &mut self,
+        path: &str,
+        name: &str,
+        field: &Value,
+        type_parameters: &[TypeParameter],
+    ) -> Option<TypeDescription> {
+        let arguments = &field["inner"]["struct_field"]["resolved_path"]["args"];
+        let argument = match arguments["angle_bracketed"]["args"].as_array() {
+            Some(arguments) if arguments.len() == 1 => arguments[0].get("type"),
+            _ => None,
+        };
+        let Some(argument) = argument else {
+            self.warn(
+                path,
+                format!("Port '{}' has no type argument like `Input<T>`.", name),
+            );
+            return None;
+        };
+
+        let parsed = self.render_type(argument).and_then(|rust_type| {
+            TypeDescription::parse(&rust_type, type_parameters)
+                .map_err(|e| Error::msg(format!("Type '{}' is not supported: {}", rust_type, e)))
+        });
+        match parsed {
+            Ok(port_type) => Some(port_type),
+            Err(e) => {
+                self.warn(path, format!("Port '{}': {}", name, e));
+                None
+            }
+        }
+    }
+
+    /// Type parameters with the trait bounds of their declaration and of `where` clauses.
+    fn type_parameters(&mut self, path: &str, generics: &Value) -> Vec<TypeParameter> {
+        let mut type_parameters = Vec::new();
+        for param in generics["params"].as_array().into_iter().flatten() {
+            let name = param["name"].as_str().unwrap_or_default().to_string();
+            let kind = &param["kind"];
+            if let Some(type_param) = kind.get("type") {
+                if type_param["is_synthetic"] == true {
+                    continue;
+                }
+                type_parameters.push(TypeParameter {
+                    constraints: self.trait_bounds(&type_param["bounds"]),
+                    name,
+                });
+            } else if kind.get("const").is_some() {
+                self.warn(
+                    path,
+                    format!("Const parameter '{}' is not supported.", name),
+                );
+            }
+        }
+
+        for predicate in generics["where_predicates"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            let Some(predicate) = predicate.get("bound_predicate") else {
+                continue;
+            };
+            let constraints = self.trait_bounds(&predicate["bounds"]);
+            let bounded = predicate["type"]["generic"].as_str();
+            match type_parameters
+                .iter_mut()
+                .find(|tp| Some(tp.name.as_str()) == bounded)
+            {
+                Some(tp) => tp.constraints.extend(constraints),
+                None => {
+                    let bounded = self
+                        .render_type(&predicate["type"])
+                        .unwrap_or_else(|_| "?".to_string());
+                    self.warn(
+                        path,
+                        format!(
+                            "Bounds of '{}' are not type parameter constraints.",
+                            bounded
+                        ),
+                    )
+                }
+            }
+        }
+        type_parameters
+    }
+
+    /// Trait bounds as constraints, lifetimes and `?Sized` are left out.
+    fn trait_bounds(&self, bounds: &Value) -> Vec<String> {
+        bounds
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|bound| bound.get("trait_bound"))
+            .filter(|bound| bound["modifier"] == "none")
+            .filter_map(|bound| self.render_path(&bound["trait"]).ok())
+            .collect()
+    }
+
+    fn inherent_constructors(
+        &mut self,
+        path: &str,
+        struct_id: &str,
+        implementation: &Value,
+        constructors: &mut HashMap<String, crate::package::Constructor>,
+    ) {
+        for item_id in implementation["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(id_key)
+        {
+            let Some(item) = self.item(&item_id).cloned() else {
+                continue;
+            };
+            let (Some(function), Some(function_name)) =
+                (item["inner"].get("function"), item["name"].as_str())
+            else {
+                continue;
+            };
+            let signature = &function["sig"];
+            let inputs: Vec<&Value> = signature["inputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .collect();
+            let has_receiver = inputs.first().is_some_and(|input| input[0] == "self");
+            if item["visibility"] != "public" || has_receiver {
+                continue;
+            }
+            let Some(fallible) = self.returns_self(&signature["output"], struct_id) else {
+                continue;
+            };
+
+            let parameters: Result<Vec<String>, Error> = inputs
+                .iter()
+                .map(|input| self.render_type(&input[1]))
+                .collect();
+            let constructor = parameters
+                .ok()
+                .and_then(|parameters| function_constructor(function_name, &parameters, fallible));
+            match constructor {
+                Some((name, constructor)) => {
+                    constructors.insert(name, constructor);
+                }
+                None => self.warn(
+                    path,
+                    format!(
+                        "Arguments of constructor '{}' cannot be inferred.",
+                        function_name
+                    ),
+                ),
+            }
+        }
+    }
+
+    /// Whether a function returns the struct (`Some(false)`) or a `Result` of it (`Some(true)`).
+    fn returns_self(&self, output: &Value, struct_id: &str) -> Option<bool> {
+        let is_self = |rust_type: &Value| {
+            rust_type["generic"] == "Self"
+                || id_key(&rust_type["resolved_path"]["id"]).as_deref() == Some(struct_id)
+        };
+        if is_self(output) {
+            return Some(false);
+        }
+        let result = &output["resolved_path"];
+        let is_result = result["path"]
+            .as_str()
+            .is_some_and(|p| p.rsplit("::").next() == Some("Result"));
+        let ok_type = &result["args"]["angle_bracketed"]["args"][0]["type"];
+        (is_result && is_self(ok_type)).then_some(true)
+    }
+
+    /// Renders a rustdoc type in Rust syntax.
+    fn render_type(&self, rust_type: &Value) -> Result<String, Error> {
+        let unsupported = || Error::msg(format!("Unsupported rustdoc type {}", rust_type));
+        let Some((kind, value)) = rust_type.as_object().and_then(|o| o.iter().next()) else {
+            return Err(unsupported());
+        };
+        Ok(match kind.as_str() {
+            "resolved_path" => self.render_path(value)?,
+            "generic" | "primitive" => value.as_str().ok_or_else(unsupported)?.to_string(),
+            "tuple" => {
+                let elements = value
+                    .as_array()
+                    .ok_or_else(unsupported)?
+                    .iter()
+                    .map(|e| self.render_type(e))
+                    .collect::<Result<Vec<String>, Error>>()?;
+                match elements.len() {
+                    1 => format!("({},)", elements[0]),
+                    _ => format!("({})", elements.join(", ")),
+                }
+            }
+            "slice" => format!("[{}]", self.render_type(value)?),
+            "array" => format!(
+                "[{}; {}]",
+                self.render_type(&value["type"])?,
+                value["len"].as_str().ok_or_else(unsupported)?
+            ),
+            "borrowed_ref" => format!(
+                "&{}{}{}",
+                value["lifetime"]
+                    .as_str()
+                    .map(|l| format!("{} ", l))
+                    .unwrap_or_default(),
+                if value["is_mutable"] == true {
+                    "mut "
+                } else {
+                    ""
+                },
+                self.render_type(&value["type"])?
+            ),
+            _ => return Err(unsupported()),
+        })
+    }
+
+    /// Renders a path with its generic arguments, local items by their public path.
+    fn render_path(&self, path: &Value) -> Result<String, Error> {
+        let written = path["path"]
+            .as_str()
+            .ok_or_else(|| Error::msg(format!("Unsupported rustdoc path {}", path)))?;
+        let mut rendered = match id_key(&path["id"]).and_then(|id| self.public_paths.get(&id)) {
+            Some(public_path) => public_path.clone(),
+            None => written.to_string(),
+        };
+
+        let arguments = &path["args"];
+        if let Some(angle_bracketed) = arguments.get("angle_bracketed") {
+            let mut parts = Vec::new();
+            for argument in angle_bracketed["args"].as_array().into_iter().flatten() {
+                if let Some(rust_type) = argument.get("type") {
+                    parts.push(self.render_type(rust_type)?);
+                } else if let Some(lifetime) = argument["lifetime"].as_str() {
+                    parts.push(lifetime.to_string());
+                }
+            }
+            for constraint in angle_bracketed["constraints"]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                if let Some(rust_type) = constraint["binding"]["equality"].get("type") {
+                    parts.push(format!(
+                        "{} = {}",
+                        constraint["name"].as_str().unwrap_or_default(),
+                        self.render_type(rust_type)?
+                    ));
+                }
+            }
+            if !parts.is_empty() {
+                rendered = format!("{}<{}>", rendered, parts.join(", "));
+            }
+        } else if let Some(parenthesized) = arguments.get("parenthesized") {
+            let inputs = parenthesized["inputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|input| self.render_type(input))
+                .collect::<Result<Vec<String>, Error>>()?;
+            rendered = format!("{}({})", rendered, inputs.join(", "));
+            if !parenthesized["output"].is_null() {
+                rendered = format!(
+                    "{} -> {}",
+                    rendered,
+                    self.render_type(&parenthesized["output"])?
+                );
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// Whether an item has an attribute like `#[name]`. Format versions list attributes as source
+/// text or as objects holding it.
+fn has_attribute(item: &Value, name: &str) -> bool {
+    item["attrs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|attribute| {
+            let text = match attribute {
+                Value::String(text) => Some(text.as_str()),
+                Value::Object(object) => object.values().find_map(|v| v.as_str()),
+                _ => None,
+            };
+            text.and_then(|t| t.trim().strip_prefix("#["))
+                .is_some_and(|t| {
+                    t.starts_with(name)
+                        && !t[name.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+                })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_rustdoc_test() {
+        let json = include_str!("../../tests/fixtures/rustdoc/nodes.json");
+        let extraction = import_rustdoc(json, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            vec![
+                "nodes::sources::Clock: Arguments of constructor 'with_interval' cannot be inferred.",
+                "nodes::sources::timer::Timer: Arguments of constructor 'with_interval' cannot be inferred."
+            ],
+            extraction
+                .warnings
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<String>>()
+        );
+
+        // The cycle through `prelude`, which re-exports the crate root, is walked once.
+        let package = extraction.into_package("nodes", "1.0.0");
+        let paths: Vec<String> = package.types().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            vec![
+                "nodes::Add",
+                "nodes::Scale",
+                "nodes::sinks::Debug",
+                "nodes::sources::Clock",
+                "nodes::sources::timer::Timer"
+            ],
+            paths
+        );
+
+        let expected: crate::package::Package = serde_json::from_str(
+            r#"{
+            "name": "nodes",
+            "version": "1.0.0",
+            "crates": {"nodes": {
+                "types": {
+                    "Add": {
+                        "inputs": {"input_1": {"type": "I1"}, "input_2": {"type": "I2"}},
+                        "outputs": {"output": {"type": "Option<O>"}},
+                        "type_parameters": [
+                            {"name": "I1", "where": ["std::ops::Add<I2, Output = O>", "Send"]},
+                            {"name": "I2", "where": []},
+                            {"name": "O", "where": []}
+                        ],
+                        "constructors": {
+                            "New": {"NewWithObserver": {"function_name": null}},
+                            "WithContext": {"NewWithObserverAndContext": {"function_name": "with_context", "fallible": true}}
+                        }
+                    },
+                    "Scale": {
+                        "inputs": {"input": {"type": "f32"}},
+                        "outputs": {
+                            "output": {"type": "Vec<f32>"},
+                            "factors": {"type": "std::collections::HashMap<String, [f32; 2]>"}
+                        },
+                        "type_parameters": null,
+                        "constructors": {"Default": "FromDefault", "Json": "FromJson"},
+                        "traits": ["Default", "nodes::serde::Deserialize<'de>"]
+                    }
+                },
+                "modules": {
+                    "sinks": {
+                        "types": {"Debug": {
+                            "inputs": {"input": {"type": "T"}},
+                            "outputs": null,
+                            "type_parameters": [{"name": "T", "where": ["std::fmt::Debug", "Clone"]}],
+                            "constructors": {"New": {"New": {"function_name": null}}, "Default": "FromDefault"},
+                            "traits": ["Clone", "Default"]
+                        }},
+                        "modules": {}
+                    },
+                    "sources": {
+                        "types": {"Clock": {
+                            "inputs": null,
+                            "outputs": {"tick": {"type": "()"}},
+                            "type_parameters": null,
+                            "constructors": {"New": {"NewWithObserver": {"function_name": null}}}
+                        }},
+                        "modules": {"timer": {
+                            "types": {"Timer": {
+                                "inputs": null,
+                                "outputs": {"tick": {"type": "()"}},
+                                "type_parameters": null,
+                                "constructors": {"New": {"NewWithObserver": {"function_name": null}}}
+                            }},
+                            "modules": {}
+                        }}
+                    }
+                }
+            }}
+        }"#,
+        )
+        .expect("wrong format.");
+        assert_eq!(expected, package);
+
+        assert!(import_rustdoc("{}", &ExtractOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use anyhow::Error;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::flow::Flow;
+use crate::package::{ArgumentConstruction, Constructor};
+use crate::package_manager::PackageManager;
+
+/// An argument referencing a shared object of the flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedReference {
+    /// Name of the shared object.
+    pub name: String,
+    /// Emitted type of the argument, e.g. `db::SharedDb`.
+    pub rust_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedObjectError {
+    /// A node and a shared object have the same name.
+    NameClash { name: String },
+    /// An argument of `owner` references a shared object that does not exist.
+    Unknown { owner: String, name: String },
+    /// The type of the shared object differs from the type of the argument referencing it.
+    TypeMismatch {
+        owner: String,
+        name: String,
+        expected: String,
+        found: String,
+    },
+    Cycle {
+        /// Shared objects forming the cycle, the first one is repeated at the end.
+        chain: Vec<String>,
+    },
+}
+
+impl fmt::Display for SharedObjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedObjectError::NameClash { name } => write!(
+                f,
+                "'{}' is the name of a node and of a shared object.",
+                name
+            ),
+            SharedObjectError::Unknown { owner, name } => write!(
+                f,
+                "'{}' references shared object '{}' which does not exist.",
+                owner, name
+            ),
+            SharedObjectError::TypeMismatch {
+                owner,
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "'{}' expects shared object '{}' to be '{}', but it is '{}'.",
+                owner, name, expected, found
+            ),
+            SharedObjectError::Cycle { chain } => {
+                write!(f, "Cyclic shared objects: {}.", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SharedObjectError {}
+
+impl Constructor {
+    /// Walks the constructors `emit_code_template` emits and collects the arguments referencing
+    /// shared objects.
+    pub fn shared_references(
+        &self,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+    ) -> Result<Vec<SharedReference>, Error> {
+        let mut references = Vec::new();
+        self.collect_shared_references(type_parameters, pack_man, &mut references)?;
+        Ok(references)
+    }
+
+    fn collect_shared_references(
+        &self,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        references: &mut Vec<SharedReference>,
+    ) -> Result<(), Error> {
+        for arg in self.arguments(pack_man, type_parameters)? {
+            match &arg.construction {
+                ArgumentConstruction::Shared(name) => {
+                    let (_, type_name, type_parameter_part) = arg.type_names(type_parameters)?;
+                    references.push(SharedReference {
+                        name: name.clone(),
+                        rust_type: format!("{}{}", type_name, type_parameter_part),
+                    });
+                }
+                ArgumentConstruction::Constructor(constructor_name) => {
+                    let (arg_constructor, _, arg_type_parameters) =
+                        arg.constructor(constructor_name, pack_man, type_parameters)?;
+                    arg_constructor.collect_shared_references(
+                        &arg_type_parameters,
+                        pack_man,
+                        references,
+                    )?;
+                }
+                ArgumentConstruction::ExistingObject() => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Flow {
+    /// The order the generated code constructs objects in: the shared objects, each after the
+    /// shared objects it references, followed by the nodes.
+    ///
+    /// Fails if a referenced shared object does not exist, has a different type than the
+    /// argument referencing it, or the shared objects reference each other in a cycle.
+    pub fn construction_order(&self, pack_man: &PackageManager) -> Result<Vec<String>, Error> {
+        let type_parameters = self.inferred_type_parameters(pack_man)?;
+
+        let mut shared_ids: Vec<&String> = self.shared.keys().collect();
+        shared_ids.sort();
+        let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+        node_ids.sort();
+
+        for id in &shared_ids {
+            if self.nodes.contains_key(*id) {
+                return Err(SharedObjectError::NameClash {
+                    name: id.to_string(),
+                }
+                .into());
+            }
+        }
+
+        // The shared objects every object references, checked against their types.
+        let mut references = HashMap::<&String, Vec<String>>::new();
+        for id in shared_ids.iter().chain(node_ids.iter()) {
+            let (constructor, _) = self.node_constructor(pack_man, id, &type_parameters[*id])?;
+            let mut names = Vec::new();
+            for reference in constructor.shared_references(&type_parameters[*id], pack_man)? {
+                let found = self.shared_type(pack_man, &reference.name, &type_parameters)?;
+                match found {
+                    None => {
+                        return Err(SharedObjectError::Unknown {
+                            owner: id.to_string(),
+                            name: reference.name,
+                        }
+                        .into())
+                    }
+                    Some(found) if found != reference.rust_type => {
+                        return Err(SharedObjectError::TypeMismatch {
+                            owner: id.to_string(),
+                            name: reference.name,
+                            expected: reference.rust_type,
+                            found,
+                        }
+                        .into())
+                    }
+                    Some(_) => names.push(reference.name),
+                }
+            }
+            names.sort();
+            names.dedup();
+            references.insert(id, names);
+        }
+
+        let mut order = Vec::new();
+        let mut done = HashSet::new();
+        let mut stack = Vec::new();
+        for id in &shared_ids {
+            order_rec(id, &references, &mut stack, &mut done, &mut order)?;
+        }
+        order.extend(node_ids.into_iter().cloned());
+
+        Ok(order)
+    }
+
+    /// The emitted type of a shared object, `None` if there is none called `name`.
+    fn shared_type(
+        &self,
+        pack_man: &PackageManager,
+        name: &str,
+        type_parameters: &HashMap<String, HashMap<String, String>>,
+    ) -> Result<Option<String>, Error> {
+        if !self.shared.contains_key(name) {
+            return Ok(None);
+        }
+        let (_, obj_desc) = self.node_constructor(pack_man, name, &type_parameters[name])?;
+        Ok(Some(format!(
+            "{}{}",
+            obj_desc.type_name, obj_desc.type_parameter_part
+        )))
+    }
+}
+
+fn order_rec(
+    id: &String,
+    references: &HashMap<&String, Vec<String>>,
+    stack: &mut Vec<String>,
+    done: &mut HashSet<String>,
+    order: &mut Vec<String>,
+) -> Result<(), SharedObjectError> {
+    if let Some(pos) = stack.iter().position(|s| s == id) {
+        let mut chain = stack[pos..].to_vec();
+        chain.push(id.clone());
+        return Err(SharedObjectError::Cycle { chain });
+    }
+    if done.contains(id) {
+        return Ok(());
+    }
+
+    stack.push(id.clone());
+    for name in &references[id] {
+        order_rec(name, references, stack, done, order)?;
+    }
+    stack.pop();
+
+    done.insert(id.clone());
+    order.push(id.clone());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::FlowCodeOptions;
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+    "nodes": {
+        "types": {
+        "Store": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"New": {"NewWithArbitraryArgs": {
+                "function_name": "new",
+                "arguments": [
+                    {"type": "nodes::Db", "name": "db", "passing": "Clone", "construction": {"Shared": "db"}}
+                ]
+            }}}
+        },
+        "Db": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {
+                "Open": {"NewWithArbitraryArgs": {
+                    "function_name": "open",
+                    "arguments": [
+                        {"type": "nodes::Pool", "name": "pool", "passing": "Reference", "construction": {"Shared": "pool"}}
+                    ]
+                }},
+                "Cyclic": {"NewWithArbitraryArgs": {
+                    "function_name": "open",
+                    "arguments": [
+                        {"type": "nodes::Db", "name": "db", "passing": "Reference", "construction": {"Shared": "db"}}
+                    ]
+                }}
+            }
+        },
+        "Pool": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"New": {"New": {}}}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    #[test]
+    fn construction_order_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        let flow = |db_constructor: &str| -> Flow {
+            serde_json::from_str(&format!(
+                r#"{{
+                    "nodes": {{
+                        "reader": {{"type": "nodes::Store", "constructor": "New"}},
+                        "writer": {{"type": "nodes::Store", "constructor": "New"}}
+                    }},
+                    "shared": {{
+                        "db": {{"type": "nodes::Db", "constructor": "{}"}},
+                        "pool": {{"type": "nodes::Pool", "constructor": "New"}}
+                    }}
+                }}"#,
+                db_constructor
+            ))
+            .expect("wrong format.")
+        };
+
+        let flow_1 = flow("Open");
+        assert_eq!(
+            vec!["pool", "db", "reader", "writer"],
+            flow_1.construction_order(&pm).unwrap()
+        );
+        let code = flow_1
+            .emit_code(&pm, &FlowCodeOptions::default())
+            .expect("emission failed.");
+        assert!(code.contains(
+            "    let pool = nodes::Pool::new();\n    \
+             let db = nodes::Db::open(&pool);\n    \
+             let reader = nodes::Store::new(db.clone());\n    \
+             let writer = nodes::Store::new(db.clone());\n"
+        ));
+
+        let error = flow("Cyclic").construction_order(&pm).unwrap_err();
+        assert_eq!(
+            Some(&SharedObjectError::Cycle {
+                chain: vec!["db".to_string(), "db".to_string()]
+            }),
+            error.downcast_ref::<SharedObjectError>()
+        );
+
+        let mut flow_2 = flow("Open");
+        flow_2.shared.remove("pool");
+        let error = flow_2.construction_order(&pm).unwrap_err();
+        assert_eq!(
+            Some(&SharedObjectError::Unknown {
+                owner: "db".to_string(),
+                name: "pool".to_string()
+            }),
+            error.downcast_ref::<SharedObjectError>()
+        );
+
+        let mut flow_3 = flow("Open");
+        let pool = flow_3.shared.remove("pool").unwrap();
+        flow_3.shared.insert("db".to_string(), pool);
+        flow_3
+            .shared
+            .insert("pool".to_string(), flow_3.nodes["reader"].clone());
+        let error = flow_3.construction_order(&pm).unwrap_err();
+        assert_eq!(
+            Some(&SharedObjectError::TypeMismatch {
+                owner: "pool".to_string(),
+                name: "db".to_string(),
+                expected: "nodes::Db".to_string(),
+                found: "nodes::Pool".to_string()
+            }),
+            error.downcast_ref::<SharedObjectError>()
+        );
+    }
+}
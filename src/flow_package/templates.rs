@@ -0,0 +1,776 @@
+use anyhow::Error;
+use handlebars::template::{Parameter, TemplateElement};
+use handlebars::{
+    no_escape, Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason,
+    Template,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::package::{Argument, Constructor, Package, Type, TypeDescription, TypeParameter};
+use crate::validate::{escape_pointer, scope_types, scopes};
+
+/// Prefix of the template variables holding the bindings of the owning type's type parameters.
+const TYPE_PARAMETER_PREFIX: &str = "type_parameter_";
+
+/// Renders the code templates of `FromCode` constructors.
+///
+/// Templates are rendered in strict mode, so referencing an undefined variable is an error, and
+/// without HTML escaping. They can use the partials of their own package and these helpers:
+///
+/// - `{{snake_case name}}`, `{{camel_case name}}` and `{{pascal_case name}}` convert
+///   identifiers, e.g. `max_value`, `maxValue` and `MaxValue`.
+/// - `{{quote text}}` emits a Rust string literal.
+/// - `{{json_path "key" ...}}` emits the expression reading the object's data from the `data`
+///   document, e.g. `data["scale"]["factor"]` for `{{json_path "factor"}}` in `scale`.
+/// - `{{construct "name" "Type" "Constructor"}}` emits the variable of an object of `Type`
+///   that is created with its constructor `Constructor` before the template, like an argument.
+#[derive(Debug, Clone)]
+pub struct TemplateRegistry {
+    /// The helpers, used for templates of packages without partials.
+    handlebars: Handlebars<'static>,
+    /// The helpers and the partials of a package, by package name and version.
+    packages: HashMap<(String, String), Handlebars<'static>>,
+}
+
+impl Default for TemplateRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemplateRegistry {
+    pub fn new() -> Self {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        handlebars.register_escape_fn(no_escape);
+        handlebars.register_helper("snake_case", Box::new(snake_case_helper));
+        handlebars.register_helper("camel_case", Box::new(camel_case_helper));
+        handlebars.register_helper("pascal_case", Box::new(pascal_case_helper));
+        handlebars.register_helper("quote", Box::new(quote_helper));
+        handlebars.register_helper("json_path", Box::new(json_path_helper));
+        handlebars.register_helper("construct", Box::new(construct_helper));
+        Self {
+            handlebars,
+            packages: HashMap::new(),
+        }
+    }
+
+    /// Registers the partials of a package. They are only visible to the templates of the same
+    /// package, so packages can use the same partial names.
+    pub fn add_partials(&mut self, package: &Package) -> Result<(), Error> {
+        if package.partials.is_empty() {
+            return Ok(());
+        }
+        let mut handlebars = self.handlebars.clone();
+        for (name, partial) in &package.partials {
+            handlebars.register_partial(name, partial)?;
+        }
+        self.packages
+            .insert((package.name.clone(), package.version.clone()), handlebars);
+        Ok(())
+    }
+
+    /// Renders a template of `package`, or one without partials if the package is unknown.
+    pub fn render<T: Serialize>(
+        &self,
+        template: &str,
+        data: &T,
+        package: Option<&Package>,
+    ) -> Result<String, Error> {
+        let handlebars = package
+            .and_then(|p| self.packages.get(&(p.name.clone(), p.version.clone())))
+            .unwrap_or(&self.handlebars);
+        Ok(handlebars.render_template(template, data)?)
+    }
+}
+
+/// A `{{construct "name" "Type" "Constructor"}}` call of a template.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Construct {
+    pub name: String,
+    pub type_name: String,
+    pub constructor: String,
+}
+
+/// What a template and the partials it includes read from their context.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct TemplateUses {
+    pub constructs: Vec<Construct>,
+    /// The keys of `json_path` calls, up to the first one that is no string literal.
+    pub json_paths: Vec<Vec<String>>,
+    /// The keys below `init_data` the template reads, no keys for all of it.
+    pub init_data: Vec<Vec<String>>,
+    /// The `type_parameter_*` variables the template reads.
+    pub type_parameters: Vec<String>,
+}
+
+/// Walks a template, following the partials of its package, and collects what it reads. The
+/// parameters of `construct` have to be string literals.
+pub(crate) fn template_uses(
+    template: &str,
+    partials: &HashMap<String, String>,
+) -> Result<TemplateUses, Error> {
+    let mut walk = TemplateWalk {
+        partials,
+        included: Vec::new(),
+        calls: Vec::new(),
+        variables: Vec::new(),
+    };
+    walk.template(&Template::compile(template)?)?;
+
+    let mut uses = TemplateUses::default();
+    for (name, params) in walk.calls {
+        match name.as_str() {
+            "construct" => match params.as_slice() {
+                [Some(name), Some(type_name), Some(constructor)] => {
+                    let construct = Construct {
+                        name: name.clone(),
+                        type_name: type_name.clone(),
+                        constructor: constructor.clone(),
+                    };
+                    if let Some(other) = uses.constructs.iter().find(|c| c.name == *name) {
+                        if *other != construct {
+                            return Err(Error::msg(format!(
+                                "'{}' is constructed twice with different types or constructors.",
+                                name
+                            )));
+                        }
+                    } else {
+                        uses.constructs.push(construct);
+                    }
+                }
+                _ => {
+                    return Err(Error::msg(
+                        "'construct' takes the string literals name, type and constructor.",
+                    ))
+                }
+            },
+            "json_path" => {
+                let keys: Vec<String> = params.into_iter().map_while(|p| p).collect();
+                if !uses.json_paths.contains(&keys) {
+                    uses.json_paths.push(keys);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for variable in walk.variables {
+        let mut segments = path_segments(&variable);
+        if segments.first().is_some_and(|s| s == "this") {
+            segments.remove(0);
+        }
+        match segments.split_first() {
+            Some((root, keys))
+                if root == "init_data" && !uses.init_data.iter().any(|k| k == keys) =>
+            {
+                uses.init_data.push(keys.to_vec());
+            }
+            Some((root, _))
+                if root.starts_with(TYPE_PARAMETER_PREFIX)
+                    && !uses.type_parameters.contains(root) =>
+            {
+                uses.type_parameters.push(root.clone());
+            }
+            _ => {}
+        }
+    }
+    Ok(uses)
+}
+
+/// The segments of a variable path, e.g. `init_data`, `a b` and `c` for `init_data.[a b]/c`.
+fn path_segments(path: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let segment;
+        if let Some(literal) = rest.strip_prefix('[') {
+            let end = literal.find(']').unwrap_or(literal.len());
+            segment = &literal[..end];
+            rest = literal.get(end + 1..).unwrap_or_default();
+        } else {
+            let end = rest.find(['.', '/']).unwrap_or(rest.len());
+            segment = &rest[..end];
+            rest = &rest[end..];
+        }
+        if !segment.is_empty() {
+            segments.push(segment.to_string());
+        }
+        rest = rest.trim_start_matches(['.', '/']);
+    }
+    segments
+}
+
+/// A helper name and its parameters, `None` for parameters that are no string literals.
+type HelperCall = (String, Vec<Option<String>>);
+
+struct TemplateWalk<'a> {
+    partials: &'a HashMap<String, String>,
+    /// Partials are included once, which also stops recursive ones.
+    included: Vec<String>,
+    calls: Vec<HelperCall>,
+    /// Paths of the variables read, e.g. `init_data.factor`.
+    variables: Vec<String>,
+}
+
+impl TemplateWalk<'_> {
+    fn template(&mut self, template: &Template) -> Result<(), Error> {
+        for element in &template.elements {
+            self.element(element)?;
+        }
+        Ok(())
+    }
+
+    fn element(&mut self, element: &TemplateElement) -> Result<(), Error> {
+        match element {
+            TemplateElement::Expression(helper)
+            | TemplateElement::HtmlExpression(helper)
+            | TemplateElement::HelperBlock(helper) => {
+                if let Some(name) = helper.name.as_name() {
+                    // Without parameters, the name may be a variable as well as a helper.
+                    if helper.params.is_empty() && helper.hash.is_empty() {
+                        self.variables.push(name.to_string());
+                    }
+                    self.calls.push((
+                        name.to_string(),
+                        helper.params.iter().map(string_literal).collect(),
+                    ));
+                }
+                self.parameters(helper.params.iter().chain(helper.hash.values()))?;
+                for template in helper.template.iter().chain(&helper.inverse) {
+                    self.template(template)?;
+                }
+            }
+            TemplateElement::PartialExpression(decorator)
+            | TemplateElement::PartialBlock(decorator) => {
+                if let Some(name) = decorator.name.as_name() {
+                    if let Some(partial) = self.partials.get(name) {
+                        if !self.included.iter().any(|i| i == name) {
+                            self.included.push(name.to_string());
+                            self.template(&Template::compile(partial)?)?;
+                        }
+                    }
+                }
+                self.parameters(decorator.params.iter().chain(decorator.hash.values()))?;
+                if let Some(template) = &decorator.template {
+                    self.template(template)?;
+                }
+            }
+            TemplateElement::DecoratorExpression(decorator)
+            | TemplateElement::DecoratorBlock(decorator) => {
+                self.parameters(decorator.params.iter().chain(decorator.hash.values()))?;
+                if let Some(template) = &decorator.template {
+                    self.template(template)?;
+                }
+            }
+            TemplateElement::RawString(_) | TemplateElement::Comment(_) => {}
+        }
+        Ok(())
+    }
+
+    fn parameters<'p>(&mut self, params: impl Iterator<Item = &'p Parameter>) -> Result<(), Error> {
+        for param in params {
+            match param {
+                Parameter::Path(_) => {
+                    if let Some(path) = param.as_name() {
+                        self.variables.push(path.to_string());
+                    }
+                }
+                Parameter::Subexpression(subexpression) => self.element(&subexpression.element)?,
+                Parameter::Name(_) | Parameter::Literal(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn string_literal(param: &Parameter) -> Option<String> {
+    match param {
+        Parameter::Literal(value) => value.as_str().map(String::from),
+        _ => None,
+    }
+}
+
+/// A problem with a template of a package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateIssue {
+    /// JSON pointer to the template in the package document.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+/// Compiles all partials and `FromCode` templates of a package and checks that the templates
+/// only use `type_parameter_<name>` variables of type parameters the type declares.
+pub fn check_templates(package: &Package) -> Vec<TemplateIssue> {
+    let mut issues = Vec::new();
+
+    let mut partials: Vec<(&String, &String)> = package.partials.iter().collect();
+    partials.sort();
+    for (name, partial) in partials {
+        if let Err(e) = Template::compile(partial) {
+            issues.push(TemplateIssue {
+                pointer: format!("/partials/{}", escape_pointer(name)),
+                message: e.to_string(),
+            });
+        }
+    }
+
+    for (pointer, _, type_desc) in scopes(package).iter().flat_map(scope_types) {
+        let mut constructors: Vec<(&String, &Constructor)> =
+            type_desc.constructors.iter().collect();
+        constructors.sort_by_key(|(name, _)| *name);
+        for (name, constructor) in constructors {
+            let Constructor::FromCode {
+                code_template,
+                arguments,
+                ..
+            } = constructor
+            else {
+                continue;
+            };
+            let pointer = format!(
+                "{}/constructors/{}/FromCode/code_template",
+                pointer,
+                escape_pointer(name)
+            );
+            issues.extend(
+                check_template(code_template, arguments, type_desc, &package.partials)
+                    .into_iter()
+                    .map(|message| TemplateIssue {
+                        pointer: pointer.clone(),
+                        message,
+                    }),
+            );
+        }
+    }
+
+    issues
+}
+
+fn check_template(
+    code_template: &str,
+    arguments: &[Argument],
+    type_desc: &Type,
+    partials: &HashMap<String, String>,
+) -> Vec<String> {
+    if let Err(e) = Template::compile(code_template) {
+        return vec![e.to_string()];
+    }
+    let uses = match template_uses(code_template, partials) {
+        Ok(uses) => uses,
+        Err(e) => return vec![e.to_string()],
+    };
+
+    let type_parameters: Vec<TypeParameter> = type_desc
+        .type_parameters
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let mut messages = Vec::new();
+    for variable in uses.type_parameters {
+        let parameter = &variable[TYPE_PARAMETER_PREFIX.len()..];
+        // `type_parameter_part` is the emitted type parameters, not a binding.
+        if variable != "type_parameter_part"
+            && !type_parameters.iter().any(|tp| tp.name == parameter)
+        {
+            messages.push(format!(
+                "'{}' refers to type parameter '{}', which the type does not declare.",
+                variable, parameter
+            ));
+        }
+    }
+
+    for construct in uses.constructs {
+        if arguments.iter().any(|arg| arg.name == construct.name) {
+            messages.push(format!(
+                "'{}' is constructed, but also an argument.",
+                construct.name
+            ));
+        }
+        if let Err(e) = TypeDescription::parse(&construct.type_name, &type_parameters) {
+            messages.push(format!(
+                "'{}' is constructed with an invalid type: {}",
+                construct.name, e
+            ));
+        }
+    }
+    messages
+}
+
+fn string_param<'a>(h: &'a Helper, name: &'static str) -> Result<&'a str, RenderErrorReason> {
+    h.param(0)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex(name, 0))?
+        .value()
+        .as_str()
+        .ok_or(RenderErrorReason::InvalidParamType("string"))
+}
+
+fn snake_case_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&snake_case(string_param(h, "snake_case")?))?;
+    Ok(())
+}
+
+fn camel_case_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&camel_case(string_param(h, "camel_case")?))?;
+    Ok(())
+}
+
+fn pascal_case_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&pascal_case(string_param(h, "pascal_case")?))?;
+    Ok(())
+}
+
+fn quote_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    // The debug representation of a string is a valid Rust string literal.
+    out.write(&format!("{:?}", string_param(h, "quote")?))?;
+    Ok(())
+}
+
+fn json_path_helper(
+    h: &Helper,
+    _: &Handlebars,
+    ctx: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let path = ctx
+        .data()
+        .get("path")
+        .and_then(|p| p.as_array())
+        .ok_or(RenderErrorReason::MissingVariable(Some("path".to_string())))?;
+    let mut expression = "data".to_string();
+    for key in path.iter().chain(h.params().iter().map(|p| p.value())) {
+        let key = key
+            .as_str()
+            .ok_or(RenderErrorReason::InvalidParamType("string"))?;
+        expression.push_str(&format!("[{:?}]", key));
+    }
+    out.write(&expression)?;
+    Ok(())
+}
+
+fn construct_helper(
+    h: &Helper,
+    _: &Handlebars,
+    ctx: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    // The object is constructed before the template like an argument, see `template_uses`.
+    let name = string_param(h, "construct")?;
+    let variable = ctx
+        .data()
+        .get("arguments")
+        .and_then(|arguments| arguments.get(name))
+        .and_then(|variable| variable.as_str())
+        .ok_or_else(|| RenderErrorReason::MissingVariable(Some(format!("arguments.{}", name))))?;
+    out.write(variable)?;
+    Ok(())
+}
+
+/// `MaxValue`, `maxValue` and `max-value` become `max_value`.
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && previous_lower {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !result.is_empty() && !result.ends_with('_') {
+                result.push('_');
+            }
+            previous_lower = false;
+        }
+    }
+    result.trim_end_matches('_').to_string()
+}
+
+/// `max_value` and `max-value` become `maxValue`.
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// `max_value` and `max-value` become `MaxValue`.
+pub(crate) fn pascal_case(name: &str) -> String {
+    snake_case(name)
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::{Namespace, ObjectDescription};
+    use crate::package_manager::PackageManager;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "partials": {
+        "declaration": "let{{mutable}} {{fully_qualified_name}}: {{type_name}}{{type_parameter_part}}"
+    },
+    "crates": {
+    "nodes": {
+        "types": {
+        "Gain": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {"Code": {"FromCode": {
+                "code_template": "{{> declaration}} = Gain::new({{arguments.offset}}, {{quote (pascal_case type_parameter_T)}}, {{json_path \"factor\"}}.as_f64(), {{construct \"range\" \"nodes::Range\" \"New\"}});",
+                "arguments": [
+                    {"type": "T", "name": "offset", "passing": "Clone", "construction": {"Constructor": "Default"}}
+                ]
+            }}}
+        },
+        "Range": {
+            "inputs": null,
+            "outputs": null,
+            "constructors": {"New": {"New": {}}}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    #[test]
+    fn templates_test() {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+
+        let constructor = &pm.get_type("nodes::Gain").unwrap().constructors["Code"];
+        let obj = ObjectDescription {
+            type_name: "nodes::Gain".to_string(),
+            type_parameter_part: "<f32>".to_string(),
+            name: "gain".to_string(),
+            is_mutable: false,
+        };
+        let mut type_params = HashMap::new();
+        type_params.insert("T".to_string(), "f32".to_string());
+        let code = constructor
+            .emit_code_template(&obj, &type_params, &pm, &Namespace::new())
+            .expect("emission failed.");
+        assert_eq!(
+            "let gain_offset:f32 = Default::default();\n\
+             \n\
+             let gain_range = nodes::Range::new();\n\
+             let gain: nodes::Gain<f32> = Gain::new(gain_offset.clone(), \"F32\", data[\"gain\"][\"factor\"].as_f64(), gain_range);",
+            code
+        );
+
+        // What `json_path` reads is part of the data document.
+        let reads = constructor
+            .json_reads(&obj, &type_params, &pm, &Namespace::new())
+            .unwrap();
+        assert_eq!(
+            vec![vec!["gain".to_string(), "factor".to_string()]],
+            reads.iter().map(|r| r.path.clone()).collect::<Vec<_>>()
+        );
+        let data = serde_json::json!({"gain": {"factor": 2.5}});
+        assert!(crate::package::validate_data(&reads, &data, &pm).is_empty());
+        let schema = constructor.data_schema(&obj, &type_params, &pm).unwrap();
+        assert!(schema["properties"]["gain"]["properties"]
+            .get("factor")
+            .is_some());
+
+        // Typos are found when the package is added.
+        let mut package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        package.version = "1.0.1".to_string();
+        let gain = package
+            .crates
+            .get_mut("nodes")
+            .unwrap()
+            .types
+            .get_mut("Gain")
+            .unwrap();
+        gain.constructors.insert(
+            "Typo".to_string(),
+            Constructor::FromCode {
+                code_template: "let {{fully_qualified_name}}: {{type_parameter_U}} = 0;"
+                    .to_string(),
+                arguments: Vec::new(),
+                package: None,
+            },
+        );
+        gain.constructors.insert(
+            "Construct".to_string(),
+            Constructor::FromCode {
+                code_template:
+                    "let {{fully_qualified_name}} = {{construct type_name \"T\" \"New\"}};"
+                        .to_string(),
+                arguments: Vec::new(),
+                package: None,
+            },
+        );
+        // Partials are checked where they are used, comments are not.
+        gain.constructors.insert(
+            "ViaPartial".to_string(),
+            Constructor::FromCode {
+                code_template:
+                    "{{!-- type_parameter_W --}}let {{fully_qualified_name}}: {{> typed}} = 0;"
+                        .to_string(),
+                arguments: Vec::new(),
+                package: None,
+            },
+        );
+        package
+            .partials
+            .insert("typed".to_string(), "{{type_parameter_V}}".to_string());
+        gain.constructors.insert(
+            "Broken".to_string(),
+            Constructor::FromCode {
+                code_template: "let {{fully_qualified_name = 0;".to_string(),
+                arguments: Vec::new(),
+                package: None,
+            },
+        );
+        let issues = check_templates(&package);
+        assert_eq!(
+            vec![
+                "/crates/nodes/types/Gain/constructors/Broken/FromCode/code_template",
+                "/crates/nodes/types/Gain/constructors/Construct/FromCode/code_template",
+                "/crates/nodes/types/Gain/constructors/Typo/FromCode/code_template",
+                "/crates/nodes/types/Gain/constructors/ViaPartial/FromCode/code_template"
+            ],
+            issues
+                .iter()
+                .map(|i| i.pointer.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert!(issues[1].message.contains("'construct'"));
+        assert!(issues[2].message.contains("'U'"));
+        assert!(issues[3].message.contains("'V'"));
+        assert!(!issues[3].message.contains("'W'"));
+        assert!(pm.add_package(package).is_err());
+
+        assert_eq!("max_value", snake_case("MaxValue"));
+        assert_eq!("maxValue", camel_case("max-value"));
+        assert_eq!("MaxValue", pascal_case("max-value"));
+    }
+
+    #[test]
+    fn partials_and_init_data_test() {
+        // Both packages use a partial `declaration`, each its own.
+        let mut pm = PackageManager::new();
+        for (name, declaration) in [
+            ("nodes", "let {{fully_qualified_name}}"),
+            ("other", "let mut {{fully_qualified_name}}"),
+        ] {
+            let mut package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+            package.name = name.to_string();
+            package
+                .partials
+                .insert("declaration".to_string(), declaration.to_string());
+            let gain = package
+                .crates
+                .get_mut("nodes")
+                .unwrap()
+                .types
+                .get_mut("Gain")
+                .unwrap();
+            gain.constructors.insert(
+                "Init".to_string(),
+                Constructor::FromCode {
+                    code_template: "{{> declaration}} = {{init_data.factor}};".to_string(),
+                    arguments: Vec::new(),
+                    package: None,
+                },
+            );
+            pm.add_package(package).unwrap();
+        }
+
+        let obj = ObjectDescription {
+            type_name: "Gain".to_string(),
+            type_parameter_part: "".to_string(),
+            name: "gain".to_string(),
+            is_mutable: false,
+        };
+        let ns = Namespace::new().with_data(serde_json::json!({"gain": {"factor": 2.5}}));
+        for (type_name, expected) in [
+            ("nodes/nodes::Gain", "let gain = 2.5;"),
+            ("other/nodes::Gain", "let mut gain = 2.5;"),
+        ] {
+            // Copies of the constructor still know their package.
+            let constructor =
+                pm.resolve_type(type_name).unwrap().type_desc.constructors["Init"].clone();
+            let code = constructor
+                .emit_code_template(&obj, &HashMap::new(), &pm, &ns.clone())
+                .expect("emission failed.");
+            assert_eq!(expected, code);
+        }
+
+        // What `init_data` reads is part of the data document, but not of the generated code.
+        let constructor = &pm
+            .resolve_type("nodes/nodes::Gain")
+            .unwrap()
+            .type_desc
+            .constructors["Init"];
+        let reads = constructor
+            .json_reads(&obj, &HashMap::new(), &pm, &Namespace::new())
+            .unwrap();
+        assert_eq!(
+            vec![vec!["gain".to_string(), "factor".to_string()]],
+            reads.iter().map(|r| r.path.clone()).collect::<Vec<_>>()
+        );
+        assert!(reads[0].at_generation);
+        let data = serde_json::json!({"gain": {"factor": 2.5}});
+        assert!(crate::package::validate_data(&reads, &data, &pm).is_empty());
+        let schema = constructor.data_schema(&obj, &HashMap::new(), &pm).unwrap();
+        assert!(schema["properties"]["gain"]["properties"]
+            .get("factor")
+            .is_some());
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::Error;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashMap;
+use syn::{Expr, FnArg, Ident, Path, Stmt, Type};
+
+use crate::flow::{Flow, FlowCodeOptions, FlowTarget};
+use crate::package::{Constructor, ErrorHandling, Namespace, ObjectDescription};
+use crate::package_manager::PackageManager;
+
+impl Constructor {
+    /// Token stream backend of `emit_code_template_with`. The code is emitted as a string and
+    /// parsed, so type names, identifiers and the code of `FromCode` templates have to be valid
+    /// Rust.
+    pub fn emit_tokens(
+        &self,
+        obj_desc: &ObjectDescription,
+        type_parameters: &HashMap<String, String>,
+        pack_man: &PackageManager,
+        namespace: &Namespace,
+        error_handling: ErrorHandling,
+    ) -> Result<TokenStream, Error> {
+        let code = self.emit_code_template_with(
+            obj_desc,
+            type_parameters,
+            pack_man,
+            namespace,
+            error_handling,
+        )?;
+        let block = syn::parse_str::<syn::Block>(&format!("{{\n{}\n}}", code)).map_err(|e| {
+            Error::msg(format!(
+                "Code template of '{}' does not emit valid statements: {}\n{}",
+                obj_desc.name, e, code
+            ))
+        })?;
+        let statements = block.stmts;
+        Ok(quote!(#(#statements)*))
+    }
+}
+
+impl Flow {
+    /// Token stream backend of [`Flow::emit_code`].
+    pub fn emit_tokens(
+        &self,
+        pack_man: &PackageManager,
+        options: &FlowCodeOptions,
+    ) -> Result<TokenStream, Error> {
+        let inferred = self.inferred_type_parameters(pack_man)?;
+        let error_handling = if options.error_type.is_some() {
+            ErrorHandling::Propagate
+        } else {
+            ErrorHandling::Panic
+        };
+
+        let namespace = Namespace::with_context_objects(pack_man).with_data(self.data_document());
+        let mut body = TokenStream::new();
+        for id in &self.construction_order(pack_man)? {
+            let (constructor, obj_desc) = self.node_constructor(pack_man, id, &inferred[id])?;
+            body.extend(constructor.emit_tokens(
+                &obj_desc,
+                &inferred[id],
+                pack_man,
+                &namespace,
+                error_handling,
+            )?);
+        }
+
+        let connect = parse::<Path>(&options.connect_function, "connect function")?;
+        for c in &self.connections {
+            let source = parse::<Ident>(&namespace.qualified_name(&c.source), "node name")?;
+            let output = parse::<Ident>(&c.output, "port name")?;
+            let target = parse::<Ident>(&namespace.qualified_name(&c.target), "node name")?;
+            let input = parse::<Ident>(&c.input, "port name")?;
+            body.extend(quote! {
+                #connect(#source.#output.clone(), #target.#input.clone());
+            });
+        }
+
+        if let Some(add_node) = &options.add_node_function {
+            let add_node = parse::<Expr>(add_node, "add node function")?;
+            let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+            node_ids.sort();
+            for id in node_ids {
+                let node = parse::<Ident>(&namespace.qualified_name(id), "node name")?;
+                body.extend(quote!(#add_node(#node);));
+            }
+        }
+
+        let return_type = match &options.error_type {
+            Some(error_type) => {
+                let error_type = parse::<Type>(error_type, "error type")?;
+                body.extend(quote!(Ok(())));
+                Some(quote!(-> Result<(), #error_type>))
+            }
+            None => None,
+        };
+
+        Ok(match &options.target {
+            FlowTarget::Function { name, parameters } => {
+                let name = parse::<Ident>(name, "function name")?;
+                let parameters = parameters
+                    .iter()
+                    .map(|p| parse::<FnArg>(p, "parameter"))
+                    .collect::<Result<Vec<FnArg>, Error>>()?;
+                quote! {
+                    #[allow(unused_variables)]
+                    pub fn #name(data: &serde_json::Value, #(#parameters),*) #return_type {
+                        #body
+                    }
+                }
+            }
+            FlowTarget::Main { prelude } => {
+                let prelude = prelude
+                    .iter()
+                    .map(|line| parse::<Stmt>(line, "prelude statement"))
+                    .collect::<Result<Vec<Stmt>, Error>>()?;
+                let data = if self.uses_json(pack_man)? {
+                    let json = self.data_document().to_string();
+                    let unwrap = match error_handling {
+                        ErrorHandling::Panic => quote!(.expect("Could not parse flow data.")),
+                        ErrorHandling::Propagate => quote!(?),
+                    };
+                    Some(quote! {
+                        let data: serde_json::Value = serde_json::from_str(#json) #unwrap;
+                    })
+                } else {
+                    None
+                };
+                quote! {
+                    fn main() #return_type {
+                        #(#prelude)*
+                        #data
+                        #body
+                    }
+                }
+            }
+        })
+    }
+
+    /// Like [`Flow::emit_code`], but emitted with [`Flow::emit_tokens`] and formatted.
+    pub fn emit_formatted_code(
+        &self,
+        pack_man: &PackageManager,
+        options: &FlowCodeOptions,
+    ) -> Result<String, Error> {
+        Ok(format!(
+            "// Generated by flowrs-package.\n\n{}",
+            format_tokens(self.emit_tokens(pack_man, options)?)?
+        ))
+    }
+}
+
+/// Formats the items of a token stream, e.g. the functions emitted by [`Flow::emit_tokens`].
+pub fn format_tokens(tokens: TokenStream) -> Result<String, Error> {
+    let file = syn::parse2::<syn::File>(tokens)?;
+    Ok(prettyplease::unparse(&file))
+}
+
+fn parse<T: syn::parse::Parse>(s: &str, what: &str) -> Result<T, Error> {
+    syn::parse_str::<T>(s).map_err(|e| Error::msg(format!("Invalid {} '{}': {}", what, s, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+    "nodes": {
+        "types": {
+        "Source": {
+            "inputs": null,
+            "outputs": {"output": {"type": "T"}},
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {"New": {"NewWithObserver": {"function_name": null}}}
+        },
+        "Scale": {
+            "inputs": {"input": {"type": "f32"}},
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {
+                "New": {"NewWithArbitraryArgs": {
+                    "function_name": "with_factor",
+                    "fallible": true,
+                    "arguments": [
+                        {"type": "f32", "name": "factor", "passing": "Move", "construction": {"Constructor": "Json"}},
+                        {"type": "nodes::Buffer", "name": "buffer", "passing": "MutableReference", "construction": {"Constructor": "Code"}}
+                    ]
+                }},
+                "Broken": {"FromCode": {"code_template": "let {{fully_qualified_name}} = ;"}}
+            }
+        },
+        "Buffer": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {"Code": {"FromCode": {"code_template": "let mut {{fully_qualified_name}} = Vec::with_capacity(16);"}}}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    const FLOW_JSON: &str = r#"
+{
+    "nodes": {
+        "source": {"type": "nodes::Source", "constructor": "New"},
+        "scale": {"type": "nodes::Scale", "constructor": "New", "data": {"factor": 2.5}}
+    },
+    "connections": [
+        {"source": "source", "output": "output", "target": "scale", "input": "input"}
+    ]
+}
+    "#;
+
+    fn package_manager() -> PackageManager {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        pm
+    }
+
+    #[test]
+    fn emit_formatted_code_test() {
+        let pm = package_manager();
+        let flow: Flow = serde_json::from_str(FLOW_JSON).expect("wrong format.");
+        let options = FlowCodeOptions {
+            target: FlowTarget::Function {
+                name: "build".to_string(),
+                parameters: vec!["change_observer: &ChangeObserver".to_string()],
+            },
+            error_type: Some("Box<dyn std::error::Error>".to_string()),
+            ..Default::default()
+        };
+        let code = flow
+            .emit_formatted_code(&pm, &options)
+            .expect("emission failed.");
+        assert_eq!(
+            r#"// Generated by flowrs-package.
+
+#[allow(unused_variables)]
+pub fn build(
+    data: &serde_json::Value,
+    change_observer: &ChangeObserver,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let scale_factor: f32 = serde_json::from_value(data["scale"]["factor"].clone())?;
+    let mut scale_buffer = Vec::with_capacity(16);
+    let scale = nodes::Scale::with_factor(scale_factor, &mut scale_buffer)?;
+    let source = nodes::Source::<f32>::new(change_observer.clone());
+    flowrs::connection::connect(source.output.clone(), scale.input.clone());
+    Ok(())
+}
+"#,
+            code
+        );
+    }
+
+    #[test]
+    fn invalid_template_test() {
+        let pm = package_manager();
+        let mut flow: Flow = serde_json::from_str(FLOW_JSON).expect("wrong format.");
+        flow.nodes.get_mut("scale").unwrap().constructor = "Broken".to_string();
+        let err = flow
+            .emit_tokens(&pm, &FlowCodeOptions::default())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Code template of 'scale' does not emit valid statements"));
+    }
+}
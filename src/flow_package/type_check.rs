@@ -0,0 +1,724 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Error;
+
+use crate::package::{trait_names_match, Type, TypeDescription};
+use crate::package_manager::{split_qualified_type_name, PackageManager};
+
+/// Substitution based unification of [`TypeDescription`]s.
+///
+/// Every [`TypeDescription::Generic`] without own type parameters is a variable that can be
+/// bound to another type description. Callers are responsible for giving generics of different
+/// scopes (e.g. different nodes) distinct names.
+#[derive(Debug, Clone, Default)]
+pub struct Unifier {
+    bindings: HashMap<String, TypeDescription>,
+}
+
+impl Unifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(&mut self, variable: &str, td: TypeDescription) -> Result<(), String> {
+        self.unify(
+            &TypeDescription::Generic {
+                name: variable.to_string(),
+                type_parameters: None,
+            },
+            &td,
+        )
+    }
+
+    /// Returns the fully substituted binding of a variable, if there is one.
+    pub fn binding(&self, variable: &str) -> Option<TypeDescription> {
+        self.bindings.get(variable).map(|td| self.resolve(td))
+    }
+
+    /// Substitutes all bound variables in `td`.
+    pub fn resolve(&self, td: &TypeDescription) -> TypeDescription {
+        if let Some(bound) = self.variable(td).and_then(|v| self.bindings.get(v)) {
+            return self.resolve(bound);
+        }
+        let mut td = td.clone();
+        for child in td.children_mut() {
+            *child = self.resolve(child);
+        }
+        td
+    }
+
+    /// Makes `a` and `b` equal by binding variables, or explains why they cannot be.
+    pub fn unify(&mut self, a: &TypeDescription, b: &TypeDescription) -> Result<(), String> {
+        let a = self.shallow_resolve(a);
+        let b = self.shallow_resolve(b);
+
+        match (self.variable(&a), self.variable(&b)) {
+            (Some(x), Some(y)) if x == y => return Ok(()),
+            (Some(x), _) => return self.bind_variable(x.to_string(), b),
+            (_, Some(y)) => return self.bind_variable(y.to_string(), a),
+            _ => {}
+        }
+
+        let mismatch = || Err(format!("'{}' does not match '{}'", a, b));
+        match (&a, &b) {
+            (
+                TypeDescription::Type {
+                    name: n1,
+                    type_parameters: p1,
+                },
+                TypeDescription::Type {
+                    name: n2,
+                    type_parameters: p2,
+                },
+            )
+            | (
+                TypeDescription::Generic {
+                    name: n1,
+                    type_parameters: p1,
+                },
+                TypeDescription::Generic {
+                    name: n2,
+                    type_parameters: p2,
+                },
+            ) => {
+                let p1: Vec<&TypeDescription> = p1.iter().flatten().map(|p| p.as_ref()).collect();
+                let p2: Vec<&TypeDescription> = p2.iter().flatten().map(|p| p.as_ref()).collect();
+                if !type_names_match(n1, n2) || p1.len() != p2.len() {
+                    return mismatch();
+                }
+                self.unify_all(&a, &b, p1.into_iter().zip(p2))
+            }
+
+            (TypeDescription::Lifetime { .. }, TypeDescription::Lifetime { .. }) => Ok(()),
+
+            (
+                TypeDescription::Reference {
+                    mutable: m1,
+                    inner: i1,
+                    ..
+                },
+                TypeDescription::Reference {
+                    mutable: m2,
+                    inner: i2,
+                    ..
+                },
+            ) if m1 == m2 => self.unify_all(&a, &b, [(i1.as_ref(), i2.as_ref())]),
+
+            (TypeDescription::Tuple { elements: e1 }, TypeDescription::Tuple { elements: e2 })
+                if e1.len() == e2.len() =>
+            {
+                self.unify_all(&a, &b, e1.iter().zip(e2))
+            }
+
+            (
+                TypeDescription::Array {
+                    element: e1,
+                    length: l1,
+                },
+                TypeDescription::Array {
+                    element: e2,
+                    length: l2,
+                },
+            ) if l1 == l2 => self.unify_all(&a, &b, [(e1.as_ref(), e2.as_ref())]),
+
+            (TypeDescription::Slice { element: e1 }, TypeDescription::Slice { element: e2 }) => {
+                self.unify_all(&a, &b, [(e1.as_ref(), e2.as_ref())])
+            }
+
+            (
+                TypeDescription::FunctionPointer {
+                    parameters: p1,
+                    return_type: r1,
+                },
+                TypeDescription::FunctionPointer {
+                    parameters: p2,
+                    return_type: r2,
+                },
+            ) if p1.len() == p2.len() && r1.is_some() == r2.is_some() => self.unify_all(
+                &a,
+                &b,
+                p1.iter().zip(p2).chain(r1.as_deref().zip(r2.as_deref())),
+            ),
+
+            (
+                TypeDescription::TraitObject { bounds: b1, .. },
+                TypeDescription::TraitObject { bounds: b2, .. },
+            ) if b1.len() == b2.len()
+                && b1
+                    .iter()
+                    .zip(b2)
+                    .all(|(x, y)| type_names_match(&x.name, &y.name)) =>
+            {
+                let (c1, c2) = (a.children(), b.children());
+                if c1.len() != c2.len() {
+                    return mismatch();
+                }
+                self.unify_all(&a, &b, c1.into_iter().zip(c2))
+            }
+
+            _ => mismatch(),
+        }
+    }
+
+    fn unify_all<'a>(
+        &mut self,
+        a: &TypeDescription,
+        b: &TypeDescription,
+        pairs: impl IntoIterator<Item = (&'a TypeDescription, &'a TypeDescription)>,
+    ) -> Result<(), String> {
+        for (x, y) in pairs {
+            self.unify(x, y)
+                .map_err(|e| format!("{} (in '{}' and '{}')", e, a, b))?;
+        }
+        Ok(())
+    }
+
+    fn variable<'a>(&self, td: &'a TypeDescription) -> Option<&'a str> {
+        match td {
+            TypeDescription::Generic {
+                name,
+                type_parameters,
+            } if type_parameters.iter().flatten().next().is_none() => Some(name),
+            _ => None,
+        }
+    }
+
+    fn shallow_resolve(&self, td: &TypeDescription) -> TypeDescription {
+        match self.variable(td).and_then(|v| self.bindings.get(v)) {
+            Some(bound) => self.shallow_resolve(bound),
+            None => td.clone(),
+        }
+    }
+
+    fn bind_variable(&mut self, variable: String, td: TypeDescription) -> Result<(), String> {
+        if self.occurs(&variable, &td) {
+            return Err(format!("'{}' cannot contain itself ('{}')", variable, td));
+        }
+        self.bindings.insert(variable, td);
+        Ok(())
+    }
+
+    fn occurs(&self, variable: &str, td: &TypeDescription) -> bool {
+        let td = self.shallow_resolve(td);
+        if self.variable(&td) == Some(variable) {
+            return true;
+        }
+        td.children().into_iter().any(|c| self.occurs(variable, c))
+    }
+}
+
+/// Package qualified names match unqualified ones with the same path.
+fn type_names_match(a: &str, b: &str) -> bool {
+    let (pa, a) = split_qualified_type_name(a);
+    let (pb, b) = split_qualified_type_name(b);
+    a == b && (pa.is_none() || pb.is_none() || pa == pb)
+}
+
+/// Renames all generics of a node so that they do not clash with generics of other nodes.
+pub(crate) fn scope_generics(td: &TypeDescription, scope: &str) -> TypeDescription {
+    let mut td = td.clone();
+    scope_generics_rec(&mut td, scope);
+    td
+}
+
+fn scope_generics_rec(td: &mut TypeDescription, scope: &str) {
+    if let TypeDescription::Generic { name, .. } = td {
+        *name = scoped_name(scope, name);
+    }
+    for child in td.children_mut() {
+        scope_generics_rec(child, scope);
+    }
+}
+
+pub(crate) fn scoped_name(scope: &str, name: &str) -> String {
+    format!("{}.{}", scope, name)
+}
+
+/// One end of a connection: a port of a node type with the (possibly partial) bindings of the
+/// node's type parameters, e.g. `T` -> `f32`.
+#[derive(Debug, Clone, Copy)]
+pub struct PortRef<'a> {
+    pub type_name: &'a str,
+    pub port: &'a str,
+    pub type_parameters: &'a HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Compatibility {
+    /// Contains the bindings inferred for type parameters that were not resolved before.
+    Compatible {
+        source_bindings: HashMap<String, String>,
+        target_bindings: HashMap<String, String>,
+    },
+    Incompatible {
+        reason: String,
+    },
+}
+
+impl Compatibility {
+    pub fn is_compatible(&self) -> bool {
+        matches!(self, Compatibility::Compatible { .. })
+    }
+}
+
+impl PackageManager {
+    /// Checks whether an output port of one node may be connected to an input port of another.
+    ///
+    /// Unresolved type parameters of both nodes are unified, so e.g. an output `Vec<T>` fits an
+    /// input `Vec<f32>` and the binding `T` -> `f32` is returned. Unknown types or ports are
+    /// errors, a type mismatch is reported as [`Compatibility::Incompatible`].
+    pub fn check_connection(
+        &self,
+        source: PortRef,
+        target: PortRef,
+    ) -> Result<Compatibility, Error> {
+        let source_type = self.resolve_type(source.type_name)?.type_desc;
+        let target_type = self.resolve_type(target.type_name)?.type_desc;
+
+        let output = source_type
+            .outputs
+            .as_ref()
+            .and_then(|o| o.get(source.port))
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "Output '{}' of type '{}' not found.",
+                    source.port, source.type_name
+                ))
+            })?;
+        let input = target_type
+            .inputs
+            .as_ref()
+            .and_then(|i| i.get(target.port))
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "Input '{}' of type '{}' not found.",
+                    target.port, target.type_name
+                ))
+            })?;
+
+        let mut unifier = Unifier::new();
+        bind_type_parameters(&mut unifier, "source", source.type_parameters)?;
+        bind_type_parameters(&mut unifier, "target", target.type_parameters)?;
+
+        let output_type = scope_generics(&output.output_type, "source");
+        let input_type = scope_generics(&input.input_type, "target");
+
+        if let Err(reason) = unifier.unify(&output_type, &input_type) {
+            return Ok(Compatibility::Incompatible {
+                reason: format!(
+                    "Output '{}' of '{}' has type '{}' but input '{}' of '{}' expects '{}': {}.",
+                    source.port,
+                    source.type_name,
+                    unifier.resolve(&output_type),
+                    target.port,
+                    target.type_name,
+                    unifier.resolve(&input_type),
+                    reason
+                ),
+            });
+        }
+
+        Ok(Compatibility::Compatible {
+            source_bindings: inferred_bindings(
+                &unifier,
+                "source",
+                source_type,
+                source.type_parameters,
+            ),
+            target_bindings: inferred_bindings(
+                &unifier,
+                "target",
+                target_type,
+                target.type_parameters,
+            ),
+        })
+    }
+}
+
+fn bind_type_parameters(
+    unifier: &mut Unifier,
+    scope: &str,
+    resolved: &HashMap<String, String>,
+) -> Result<(), Error> {
+    for (name, type_name) in resolved {
+        let td = TypeDescription::from_str(type_name)?;
+        unifier
+            .bind(&scoped_name(scope, name), td)
+            .map_err(Error::msg)?;
+    }
+    Ok(())
+}
+
+/// Bindings of declared, previously unresolved type parameters that contain no variables.
+fn inferred_bindings(
+    unifier: &Unifier,
+    scope: &str,
+    type_desc: &Type,
+    resolved: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut bindings = HashMap::new();
+    for tp in type_desc.type_parameters.iter().flatten() {
+        if resolved.contains_key(&tp.name) {
+            continue;
+        }
+        if let Some(td) = unifier.binding(&scoped_name(scope, &tp.name)) {
+            if !contains_generics(&td) {
+                bindings.insert(tp.name.clone(), td.to_string());
+            }
+        }
+    }
+    bindings
+}
+
+pub(crate) fn contains_generics(td: &TypeDescription) -> bool {
+    matches!(td, TypeDescription::Generic { .. })
+        || td.children().into_iter().any(contains_generics)
+}
+
+/// Traits that tuples and arrays implement if all their elements do.
+const STRUCTURAL_TRAITS: [&str; 12] = [
+    "Clone",
+    "Copy",
+    "Debug",
+    "Default",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+    "Send",
+    "Sync",
+    "serde::Serialize",
+];
+
+/// A type parameter bound to a type that does not implement one of its `where` constraints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintViolation {
+    pub parameter: String,
+    pub bound_type: String,
+    pub trait_name: String,
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Type parameter '{}' is bound to '{}', which does not implement '{}'.",
+            self.parameter, self.bound_type, self.trait_name
+        )
+    }
+}
+
+impl std::error::Error for ConstraintViolation {}
+
+impl PackageManager {
+    /// Whether a type implements a trait according to the registered [`Type::traits`].
+    ///
+    /// Tuples, arrays and the type arguments of generic types must implement the derivable std
+    /// traits (and `Deserialize`) as well. Shared references are `Clone` and `Copy`, mutable ones
+    /// are neither, and otherwise references forward to the referenced type.
+    pub fn implements_trait(&self, td: &TypeDescription, trait_name: &str) -> bool {
+        let structural = || {
+            STRUCTURAL_TRAITS
+                .iter()
+                .chain(["serde::Deserialize"].iter())
+                .any(|t| trait_names_match(t, trait_name))
+        };
+
+        match td {
+            TypeDescription::Type {
+                name,
+                type_parameters,
+            } => {
+                self.resolve_type(name)
+                    .is_ok_and(|r| r.type_desc.implements(trait_name))
+                    && (!structural()
+                        || type_parameters
+                            .iter()
+                            .flatten()
+                            .all(|tp| self.implements_trait(tp, trait_name)))
+            }
+            TypeDescription::Tuple { elements } => {
+                structural()
+                    && elements
+                        .iter()
+                        .all(|e| self.implements_trait(e, trait_name))
+            }
+            TypeDescription::Array { element, .. } => {
+                structural() && self.implements_trait(element, trait_name)
+            }
+            TypeDescription::Reference { inner, mutable, .. } => {
+                if trait_names_match("Clone", trait_name) || trait_names_match("Copy", trait_name) {
+                    !mutable
+                } else if trait_names_match("Default", trait_name)
+                    || trait_names_match("serde::Deserialize", trait_name)
+                {
+                    false
+                } else {
+                    self.implements_trait(inner, trait_name)
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks the resolved type parameters of a type against their `where` constraints.
+    ///
+    /// Constraints are trait names; a leading `T::` or `T:` naming the parameter is ignored.
+    /// Unresolved parameters are not checked.
+    pub fn check_type_parameter_constraints(
+        &self,
+        type_desc: &Type,
+        resolved: &HashMap<String, String>,
+    ) -> Result<(), Vec<ConstraintViolation>> {
+        let mut violations = Vec::new();
+
+        for tp in type_desc.type_parameters.iter().flatten() {
+            let Some(bound_type) = resolved.get(&tp.name) else {
+                continue;
+            };
+            let td = TypeDescription::from_str(bound_type).ok();
+
+            for constraint in &tp.constraints {
+                let trait_name = constraint
+                    .strip_prefix(&format!("{}::", tp.name))
+                    .or_else(|| constraint.strip_prefix(&format!("{}:", tp.name)))
+                    .unwrap_or(constraint)
+                    .trim();
+                if !td
+                    .as_ref()
+                    .is_some_and(|td| self.implements_trait(td, trait_name))
+                {
+                    violations.push(ConstraintViolation {
+                        parameter: tp.name.clone(),
+                        bound_type: bound_type.clone(),
+                        trait_name: trait_name.to_string(),
+                    });
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "crates": {
+    "nodes": {
+        "types": {
+        "Source": {
+            "inputs": null,
+            "outputs": {"out": {"type": "Vec<T>"}},
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {}
+        },
+        "FloatSink": {
+            "inputs": {"in": {"type": "Vec<f32>"}},
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {}
+        },
+        "TextSink": {
+            "inputs": {"in": {"type": "String"}},
+            "outputs": null,
+            "type_parameters": null,
+            "constructors": {}
+        },
+        "Wrapper": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {},
+            "traits": ["Clone", "Display"]
+        },
+        "PairSink": {
+            "inputs": {"in": {"type": "Vec<(U, U)>"}},
+            "outputs": null,
+            "type_parameters": [{"name": "U", "where": []}],
+            "constructors": {}
+        }
+        },
+        "modules": {}
+    }
+    }
+}
+        "#;
+
+    fn package_manager() -> PackageManager {
+        let mut pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        pm.add_package(package).unwrap();
+        pm
+    }
+
+    fn port<'a>(
+        type_name: &'a str,
+        port: &'a str,
+        type_parameters: &'a HashMap<String, String>,
+    ) -> PortRef<'a> {
+        PortRef {
+            type_name,
+            port,
+            type_parameters,
+        }
+    }
+
+    #[test]
+    fn check_connection_test() {
+        let pm = package_manager();
+        let none = HashMap::new();
+        let mut t_string = HashMap::new();
+        t_string.insert("T".to_string(), "String".to_string());
+
+        let mut expected = HashMap::new();
+        expected.insert("T".to_string(), "f32".to_string());
+        assert_eq!(
+            Compatibility::Compatible {
+                source_bindings: expected,
+                target_bindings: HashMap::new()
+            },
+            pm.check_connection(
+                port("nodes::Source", "out", &none),
+                port("nodes::FloatSink", "in", &none)
+            )
+            .unwrap()
+        );
+
+        let Compatibility::Incompatible { reason } = pm
+            .check_connection(
+                port("nodes::Source", "out", &t_string),
+                port("nodes::FloatSink", "in", &none),
+            )
+            .unwrap()
+        else {
+            panic!("String must not fit f32.");
+        };
+        assert!(
+            reason.contains("'String' does not match 'f32'"),
+            "{}",
+            reason
+        );
+
+        assert!(!pm
+            .check_connection(
+                port("nodes::Source", "out", &none),
+                port("nodes::TextSink", "in", &none)
+            )
+            .unwrap()
+            .is_compatible());
+
+        let Compatibility::Compatible {
+            source_bindings,
+            target_bindings,
+        } = pm
+            .check_connection(
+                port("nodes::Source", "out", &none),
+                port("nodes::PairSink", "in", &none),
+            )
+            .unwrap()
+        else {
+            panic!("generic ports must unify.");
+        };
+        assert!(source_bindings.is_empty() && target_bindings.is_empty());
+
+        assert!(pm
+            .check_connection(
+                port("nodes::Source", "nope", &none),
+                port("nodes::FloatSink", "in", &none)
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn check_type_parameter_constraints_test() {
+        let pm = package_manager();
+        let t: Type = serde_json::from_str(
+            r#"{
+                "inputs": null,
+                "outputs": null,
+                "type_parameters": [
+                    {"name": "T", "where": ["Default", "T::Copy"]},
+                    {"name": "U", "where": ["std::hash::Hash"]}
+                ],
+                "constructors": {}
+            }"#,
+        )
+        .unwrap();
+
+        let mut resolved = HashMap::new();
+        resolved.insert("T".to_string(), "(u8, [f32; 2])".to_string());
+        resolved.insert("U".to_string(), "char".to_string());
+        assert_eq!(Ok(()), pm.check_type_parameter_constraints(&t, &resolved));
+
+        resolved.insert("T".to_string(), "nodes::TextSink".to_string());
+        resolved.insert("U".to_string(), "f64".to_string());
+        let violations = pm
+            .check_type_parameter_constraints(&t, &resolved)
+            .unwrap_err();
+        let violated: Vec<&str> = violations.iter().map(|v| v.trait_name.as_str()).collect();
+        assert_eq!(vec!["Default", "Copy", "std::hash::Hash"], violated);
+
+        let text_sink = TypeDescription::from_str("nodes::TextSink").unwrap();
+        assert!(!pm.implements_trait(&text_sink, "Clone"));
+        assert!(pm.implements_trait(
+            &TypeDescription::from_str("&nodes::TextSink").unwrap(),
+            "Clone"
+        ));
+    }
+
+    #[test]
+    fn implements_trait_test() {
+        let pm = package_manager();
+        let implements = |type_name: &str, trait_name: &str| {
+            pm.implements_trait(&TypeDescription::from_str(type_name).unwrap(), trait_name)
+        };
+
+        assert!(implements("&u8", "Copy"));
+        assert!(!implements("&mut u8", "Copy"));
+        assert!(!implements("&mut u8", "Clone"));
+        assert!(implements("&mut u8", "Debug"));
+
+        assert!(implements("nodes::Wrapper<u8>", "Clone"));
+        assert!(!implements("nodes::Wrapper<nodes::TextSink>", "Clone"));
+        assert!(!implements(
+            "nodes::Wrapper<(u8, nodes::TextSink)>",
+            "Clone"
+        ));
+        // Only the derivable traits depend on the type arguments.
+        assert!(implements("nodes::Wrapper<nodes::TextSink>", "Display"));
+    }
+
+    #[test]
+    fn unifier_test() {
+        let mut unifier = Unifier::new();
+        let mut a = TypeDescription::from_str("(A, [B; 2], fn(&A) -> C)").unwrap();
+        a.mark_generics(&["A", "B", "C"]);
+        let mut b = TypeDescription::from_str("(u8, [C; 2], fn(&u8) -> bool)").unwrap();
+        b.mark_generics(&["C"]);
+
+        unifier.unify(&a, &b).unwrap();
+        assert_eq!(
+            "(u8, [bool; 2], fn(&u8) -> bool)",
+            unifier.resolve(&a).to_string()
+        );
+
+        let mut t = TypeDescription::from_str("Vec<T>").unwrap();
+        t.mark_generics(&["T"]);
+        let mut unifier = Unifier::new();
+        assert!(unifier.bind("T", t).is_err());
+    }
+}
@@ -0,0 +1,773 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Error;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::package::{TraitBound, TypeDescription, TypeParameter};
+use crate::package_manager::split_qualified_type_name;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParseError {
+    pub message: String,
+    /// Byte offset in the parsed string.
+    pub position: usize,
+}
+
+impl fmt::Display for TypeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for TypeParseError {}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    generics: Vec<&'a str>,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, TypeParseError> {
+        Err(TypeParseError {
+            message: message.to_string(),
+            position: self.pos,
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), TypeParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            self.error(&format!("Expected '{}'", token))
+        }
+    }
+
+    /// A path segment. Package names may contain `-` and are separated from the path by `/`.
+    fn ident(&mut self) -> Result<&'a str, TypeParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let mut end = 0;
+        for (i, c) in rest.char_indices() {
+            let valid = c.is_alphanumeric()
+                || c == '_'
+                || (i > 0 && c == '/')
+                || (i > 0 && c == '-' && !rest[i..].starts_with("->"));
+            if !valid {
+                break;
+            }
+            end = i + c.len_utf8();
+        }
+        if end == 0 {
+            return self.error("Expected identifier");
+        }
+        self.pos += end;
+        Ok(&rest[..end])
+    }
+
+    fn lifetime(&mut self) -> Result<Option<String>, TypeParseError> {
+        if self.eat("'") {
+            Ok(Some(self.ident()?.to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Eats a keyword only if it is not the prefix of a longer identifier.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let is_keyword = rest.starts_with(keyword)
+            && !rest[keyword.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        if is_keyword {
+            self.pos += keyword.len();
+        }
+        is_keyword
+    }
+
+    fn parse_type(&mut self) -> Result<TypeDescription, TypeParseError> {
+        if self.eat("&") {
+            let lifetime = self.lifetime()?;
+            let mutable = self.eat_keyword("mut");
+            return Ok(TypeDescription::Reference {
+                lifetime,
+                mutable,
+                inner: Box::new(self.parse_type()?),
+            });
+        }
+
+        if self.eat("(") {
+            return self.parse_tuple();
+        }
+
+        if self.eat("[") {
+            let element = Box::new(self.parse_type()?);
+            if self.eat("]") {
+                return Ok(TypeDescription::Slice { element });
+            }
+            self.expect(";")?;
+            let length = self.length()?;
+            self.expect("]")?;
+            return Ok(TypeDescription::Array { element, length });
+        }
+
+        if self.eat_keyword("fn") {
+            self.expect("(")?;
+            let (parameters, return_type) = self.parse_signature()?;
+            return Ok(TypeDescription::FunctionPointer {
+                parameters,
+                return_type,
+            });
+        }
+
+        if self.eat_keyword("dyn") {
+            return self.parse_trait_object();
+        }
+
+        self.parse_path()
+    }
+
+    fn length(&mut self) -> Result<usize, TypeParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        match rest[..digits].parse() {
+            Ok(length) => {
+                self.pos += digits;
+                Ok(length)
+            }
+            Err(_) => self.error("Expected array length"),
+        }
+    }
+
+    /// Parses after the opening `(`: the unit type, a parenthesized type or a tuple.
+    fn parse_tuple(&mut self) -> Result<TypeDescription, TypeParseError> {
+        if self.eat(")") {
+            return Ok(TypeDescription::Type {
+                name: "()".to_string(),
+                type_parameters: None,
+            });
+        }
+
+        let first = self.parse_type()?;
+        if self.eat(")") {
+            return Ok(first);
+        }
+        self.expect(",")?;
+
+        let mut elements = vec![first];
+        elements.append(&mut self.parse_list(")")?);
+        Ok(TypeDescription::Tuple { elements })
+    }
+
+    /// Parses after the opening `(` of a function signature: the parameters and `-> R`.
+    #[allow(clippy::type_complexity)]
+    fn parse_signature(
+        &mut self,
+    ) -> Result<(Vec<TypeDescription>, Option<Box<TypeDescription>>), TypeParseError> {
+        let parameters = self.parse_list(")")?;
+        let return_type = if self.eat("->") {
+            Some(Box::new(self.parse_type()?))
+        } else {
+            None
+        };
+        Ok((parameters, return_type))
+    }
+
+    fn parse_trait_object(&mut self) -> Result<TypeDescription, TypeParseError> {
+        let mut bounds = Vec::new();
+        let mut lifetime = None;
+
+        loop {
+            if let Some(l) = self.lifetime()? {
+                lifetime = Some(l);
+            } else {
+                bounds.push(self.parse_trait_bound()?);
+            }
+            if !self.eat("+") {
+                break;
+            }
+        }
+
+        if bounds.is_empty() {
+            return self.error("Expected at least one trait");
+        }
+        Ok(TypeDescription::TraitObject { bounds, lifetime })
+    }
+
+    fn parse_trait_bound(&mut self) -> Result<TraitBound, TypeParseError> {
+        let mut segments = vec![self.ident()?];
+        while self.eat("::") {
+            segments.push(self.ident()?);
+        }
+
+        let mut bound = TraitBound {
+            name: segments.join("::"),
+            type_parameters: None,
+            parameters: None,
+            return_type: None,
+        };
+        if self.eat("<") {
+            bound.type_parameters = Some(self.parse_list(">")?);
+        } else if self.eat("(") {
+            let (parameters, return_type) = self.parse_signature()?;
+            bound.parameters = Some(parameters);
+            bound.return_type = return_type;
+        }
+        Ok(bound)
+    }
+
+    fn parse_path(&mut self) -> Result<TypeDescription, TypeParseError> {
+        let mut segments = vec![self.ident()?];
+        while self.eat("::") {
+            segments.push(self.ident()?);
+        }
+        let name = segments.join("::");
+
+        let type_parameters = if self.eat("<") {
+            let params = self.parse_generic_arguments()?;
+            Some(params.into_iter().map(Box::new).collect())
+        } else {
+            None
+        };
+
+        if segments.len() == 1 && self.generics.contains(&segments[0]) {
+            Ok(TypeDescription::Generic {
+                name,
+                type_parameters,
+            })
+        } else {
+            Ok(TypeDescription::Type {
+                name,
+                type_parameters,
+            })
+        }
+    }
+
+    /// Like [`Parser::parse_list`] up to `>`, but also accepts lifetimes.
+    fn parse_generic_arguments(&mut self) -> Result<Vec<TypeDescription>, TypeParseError> {
+        let mut types = Vec::new();
+        while !self.eat(">") {
+            match self.lifetime()? {
+                Some(name) => types.push(TypeDescription::Lifetime { name }),
+                None => types.push(self.parse_type()?),
+            }
+            if !self.eat(",") && self.peek() != Some('>') {
+                return self.error("Expected ',' or '>'");
+            }
+        }
+        Ok(types)
+    }
+
+    /// Comma separated types up to and including `close`. A trailing comma is allowed.
+    fn parse_list(&mut self, close: &str) -> Result<Vec<TypeDescription>, TypeParseError> {
+        let mut types = Vec::new();
+        while !self.eat(close) {
+            types.push(self.parse_type()?);
+            if !self.eat(",") && self.peek() != close.chars().next() {
+                return self.error(&format!("Expected ',' or '{}'", close));
+            }
+        }
+        Ok(types)
+    }
+}
+
+impl TypeDescription {
+    /// Parses a Rust type such as `Vec<HashMap<String, Option<T>>>`, `&'a mut [u8]`,
+    /// `(f32, [u8; 4])`, `fn(u8) -> bool` or `Box<dyn Fn(i32) -> i32 + Send>`.
+    ///
+    /// Single-segment names that match one of the `type_parameters` become
+    /// [`TypeDescription::Generic`], everything else is a concrete [`TypeDescription::Type`].
+    pub fn parse(s: &str, type_parameters: &[TypeParameter]) -> Result<Self, TypeParseError> {
+        let mut parser = Parser {
+            input: s,
+            pos: 0,
+            generics: type_parameters.iter().map(|tp| tp.name.as_str()).collect(),
+        };
+        let td = parser.parse_type()?;
+        parser.skip_whitespace();
+        if parser.pos != s.len() {
+            return parser.error("Unexpected trailing input");
+        }
+        Ok(td)
+    }
+
+    /// All type descriptions directly nested in this one.
+    pub fn children(&self) -> Vec<&TypeDescription> {
+        match self {
+            TypeDescription::Type {
+                type_parameters, ..
+            }
+            | TypeDescription::Generic {
+                type_parameters, ..
+            } => type_parameters
+                .iter()
+                .flatten()
+                .map(|tp| tp.as_ref())
+                .collect(),
+            TypeDescription::Lifetime { .. } => vec![],
+            TypeDescription::Reference { inner, .. } => vec![inner.as_ref()],
+            TypeDescription::Tuple { elements } => elements.iter().collect(),
+            TypeDescription::Array { element, .. } | TypeDescription::Slice { element } => {
+                vec![element.as_ref()]
+            }
+            TypeDescription::FunctionPointer {
+                parameters,
+                return_type,
+            } => parameters.iter().chain(return_type.as_deref()).collect(),
+            TypeDescription::TraitObject { bounds, .. } => bounds
+                .iter()
+                .flat_map(|b| {
+                    b.type_parameters
+                        .iter()
+                        .flatten()
+                        .chain(b.parameters.iter().flatten())
+                        .chain(b.return_type.as_deref())
+                })
+                .collect(),
+        }
+    }
+
+    /// All type descriptions directly nested in this one.
+    pub fn children_mut(&mut self) -> Vec<&mut TypeDescription> {
+        match self {
+            TypeDescription::Type {
+                type_parameters, ..
+            }
+            | TypeDescription::Generic {
+                type_parameters, ..
+            } => type_parameters
+                .iter_mut()
+                .flatten()
+                .map(|tp| tp.as_mut())
+                .collect(),
+            TypeDescription::Lifetime { .. } => vec![],
+            TypeDescription::Reference { inner, .. } => vec![inner.as_mut()],
+            TypeDescription::Tuple { elements } => elements.iter_mut().collect(),
+            TypeDescription::Array { element, .. } | TypeDescription::Slice { element } => {
+                vec![element.as_mut()]
+            }
+            TypeDescription::FunctionPointer {
+                parameters,
+                return_type,
+            } => parameters
+                .iter_mut()
+                .chain(return_type.as_deref_mut())
+                .collect(),
+            TypeDescription::TraitObject { bounds, .. } => bounds
+                .iter_mut()
+                .flat_map(|b| {
+                    b.type_parameters
+                        .iter_mut()
+                        .flatten()
+                        .chain(b.parameters.iter_mut().flatten())
+                        .chain(b.return_type.as_deref_mut())
+                })
+                .collect(),
+        }
+    }
+
+    /// Turns concrete types named like one of `names` into generics.
+    pub fn mark_generics(&mut self, names: &[&str]) {
+        if let TypeDescription::Type {
+            name,
+            type_parameters,
+        } = self
+        {
+            if names.contains(&name.as_str()) {
+                *self = TypeDescription::Generic {
+                    name: std::mem::take(name),
+                    type_parameters: type_parameters.take(),
+                };
+            }
+        }
+
+        for child in self.children_mut() {
+            child.mark_generics(names);
+        }
+    }
+
+    /// Emits the Rust type with all generics replaced by their resolved types and package
+    /// qualifications removed.
+    pub fn emit_rust_type(&self, resolved: &HashMap<String, String>) -> Result<String, Error> {
+        self.render(&Rendering::Emit(resolved))
+    }
+
+    /// Emits `<A, B>` for non-empty type parameters and an empty string otherwise.
+    #[allow(clippy::vec_box)]
+    pub fn emit_type_parameters_part(
+        type_parameters: &Option<Vec<Box<TypeDescription>>>,
+        resolved: &HashMap<String, String>,
+    ) -> Result<String, Error> {
+        Self::render_type_parameters_part(type_parameters, &Rendering::Emit(resolved))
+    }
+
+    #[allow(clippy::vec_box)]
+    fn render_type_parameters_part(
+        type_parameters: &Option<Vec<Box<TypeDescription>>>,
+        rendering: &Rendering,
+    ) -> Result<String, Error> {
+        match type_parameters {
+            Some(params) if !params.is_empty() => Ok(format!(
+                "<{}>",
+                Self::render_list(params.iter().map(|tp| tp.as_ref()), rendering)?
+            )),
+            _ => Ok("".to_string()),
+        }
+    }
+
+    fn render_list<'a>(
+        types: impl Iterator<Item = &'a TypeDescription>,
+        rendering: &Rendering,
+    ) -> Result<String, Error> {
+        Ok(types
+            .map(|t| t.render(rendering))
+            .collect::<Result<Vec<String>, Error>>()?
+            .join(", "))
+    }
+
+    fn render_return_type(
+        return_type: &Option<Box<TypeDescription>>,
+        rendering: &Rendering,
+    ) -> Result<String, Error> {
+        match return_type {
+            Some(r) => Ok(format!(" -> {}", r.render(rendering)?)),
+            None => Ok("".to_string()),
+        }
+    }
+
+    fn render(&self, rendering: &Rendering) -> Result<String, Error> {
+        match self {
+            TypeDescription::Type {
+                name,
+                type_parameters,
+            } => Ok(format!(
+                "{}{}",
+                rendering.type_name(name),
+                Self::render_type_parameters_part(type_parameters, rendering)?
+            )),
+
+            TypeDescription::Generic {
+                name,
+                type_parameters,
+            } => {
+                let generic = match rendering {
+                    Rendering::Source => name.clone(),
+                    Rendering::Emit(resolved) => {
+                        let resolved_name = resolved.get(name).ok_or_else(|| {
+                            Error::msg(format!("Generic type '{}' was not resolved.", name))
+                        })?;
+                        TypeDescription::from_str(resolved_name)?.emit_rust_type(&HashMap::new())?
+                    }
+                };
+                Ok(format!(
+                    "{}{}",
+                    generic,
+                    Self::render_type_parameters_part(type_parameters, rendering)?
+                ))
+            }
+
+            TypeDescription::Lifetime { name } => Ok(format!("'{}", name)),
+
+            TypeDescription::Reference {
+                lifetime,
+                mutable,
+                inner,
+            } => Ok(format!(
+                "&{}{}{}",
+                lifetime
+                    .as_ref()
+                    .map(|l| format!("'{} ", l))
+                    .unwrap_or_default(),
+                if *mutable { "mut " } else { "" },
+                inner.render(rendering)?
+            )),
+
+            TypeDescription::Tuple { elements } => Ok(format!(
+                "({}{})",
+                Self::render_list(elements.iter(), rendering)?,
+                if elements.len() == 1 { "," } else { "" }
+            )),
+
+            TypeDescription::Array { element, length } => {
+                Ok(format!("[{}; {}]", element.render(rendering)?, length))
+            }
+
+            TypeDescription::Slice { element } => Ok(format!("[{}]", element.render(rendering)?)),
+
+            TypeDescription::FunctionPointer {
+                parameters,
+                return_type,
+            } => Ok(format!(
+                "fn({}){}",
+                Self::render_list(parameters.iter(), rendering)?,
+                Self::render_return_type(return_type, rendering)?
+            )),
+
+            TypeDescription::TraitObject { bounds, lifetime } => {
+                let mut parts = Vec::new();
+                for bound in bounds {
+                    let mut part = rendering.type_name(&bound.name).to_string();
+                    if let Some(params) = &bound.type_parameters {
+                        part.push_str(&format!(
+                            "<{}>",
+                            Self::render_list(params.iter(), rendering)?
+                        ));
+                    }
+                    if let Some(params) = &bound.parameters {
+                        part.push_str(&format!(
+                            "({}){}",
+                            Self::render_list(params.iter(), rendering)?,
+                            Self::render_return_type(&bound.return_type, rendering)?
+                        ));
+                    }
+                    parts.push(part);
+                }
+                if let Some(l) = lifetime {
+                    parts.push(format!("'{}", l));
+                }
+                Ok(format!("dyn {}", parts.join(" + ")))
+            }
+        }
+    }
+}
+
+enum Rendering<'a> {
+    /// Round-trippable Rust syntax: generics and package qualifications are kept.
+    Source,
+    /// Code to emit: generics are resolved, package qualifications are dropped.
+    Emit(&'a HashMap<String, String>),
+}
+
+impl Rendering<'_> {
+    fn type_name<'n>(&self, name: &'n str) -> &'n str {
+        match self {
+            Rendering::Source => name,
+            Rendering::Emit(_) => split_qualified_type_name(name).1,
+        }
+    }
+}
+
+impl fmt::Display for TypeDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = self.render(&Rendering::Source).map_err(|_| fmt::Error)?;
+        write!(f, "{}", source)
+    }
+}
+
+impl FromStr for TypeDescription {
+    type Err = TypeParseError;
+
+    /// Parses a type without any declared generics.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, &[])
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TypeDescriptionRepr {
+    Compact(String),
+    Full(#[serde(deserialize_with = "TypeDescription::deserialize")] TypeDescription),
+}
+
+/// Accepts both the object form and the compact Rust syntax. Generics in the compact form are
+/// marked once the owning [`crate::package::Type`] is deserialized.
+impl<'de> Deserialize<'de> for TypeDescription {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match TypeDescriptionRepr::deserialize(deserializer)? {
+            TypeDescriptionRepr::Compact(s) => {
+                TypeDescription::from_str(&s).map_err(D::Error::custom)
+            }
+            TypeDescriptionRepr::Full(td) => Ok(td),
+        }
+    }
+}
+
+impl Serialize for TypeDescription {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TypeDescription::serialize(self, serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Type;
+
+    fn generic(name: &str) -> TypeParameter {
+        TypeParameter {
+            name: name.to_string(),
+            constraints: vec![],
+        }
+    }
+
+    #[test]
+    fn parse_and_print_test() {
+        let td = TypeDescription::parse("Vec< HashMap<String,Option<T>> >", &[generic("T")])
+            .expect("parse failed.");
+        assert_eq!("Vec<HashMap<String, Option<T>>>", td.to_string());
+
+        let TypeDescription::Type {
+            type_parameters: Some(vec_params),
+            ..
+        } = &td
+        else {
+            panic!("expected concrete Vec.");
+        };
+        let TypeDescription::Type {
+            type_parameters: Some(map_params),
+            ..
+        } = vec_params[0].as_ref()
+        else {
+            panic!("expected concrete HashMap.");
+        };
+        assert_eq!(
+            TypeDescription::Type {
+                name: "Option".to_string(),
+                type_parameters: Some(vec![Box::new(TypeDescription::Generic {
+                    name: "T".to_string(),
+                    type_parameters: None
+                })])
+            },
+            *map_params[1]
+        );
+
+        let mut resolved = HashMap::new();
+        resolved.insert("T".to_string(), "my_package/my_crate::Value".to_string());
+        assert_eq!(
+            "Vec<HashMap<String, Option<my_crate::Value>>>",
+            td.emit_rust_type(&resolved).unwrap()
+        );
+        assert!(td.emit_rust_type(&HashMap::new()).is_err());
+
+        assert!(TypeDescription::from_str("Vec<i32").is_err());
+        assert!(TypeDescription::from_str("Vec<i32>>").is_err());
+        assert_eq!(
+            "built-in/primitives::i32",
+            TypeDescription::from_str("built-in/primitives::i32")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn parse_structural_types_test() {
+        let round_trips = [
+            "(u8, u16)",
+            "(u8,)",
+            "[f32; 4]",
+            "&'static str",
+            "&mut [T]",
+            "Box<dyn Fn(i32) -> i32>",
+            "Box<dyn std::error::Error + Send + Sync + 'static>",
+            "fn(u8) -> bool",
+            "fn()",
+            "Cow<'a, [(T, u8); 2]>",
+            "()",
+        ];
+        for rt in round_trips {
+            let td = TypeDescription::parse(rt, &[generic("T")]).expect(rt);
+            assert_eq!(rt, td.to_string());
+        }
+
+        assert_eq!(
+            TypeDescription::Reference {
+                lifetime: Some("static".to_string()),
+                mutable: false,
+                inner: Box::new(TypeDescription::Type {
+                    name: "str".to_string(),
+                    type_parameters: None
+                })
+            },
+            TypeDescription::from_str("&'static str").unwrap()
+        );
+        assert_eq!(
+            TypeDescription::from_str("u8").unwrap(),
+            TypeDescription::from_str("(u8)").unwrap()
+        );
+        assert!(TypeDescription::from_str("[u8; N]").is_err());
+        assert!(TypeDescription::from_str("dyn").is_err());
+
+        let td =
+            TypeDescription::parse("Box<dyn FnMut(&T) -> (T, [T; 2])>", &[generic("T")]).unwrap();
+        let mut resolved = HashMap::new();
+        resolved.insert("T".to_string(), "f32".to_string());
+        assert_eq!(
+            "Box<dyn FnMut(&f32) -> (f32, [f32; 2])>",
+            td.emit_rust_type(&resolved).unwrap()
+        );
+
+        let mut td = TypeDescription::from_str("fn(&[T]) -> T").unwrap();
+        td.mark_generics(&["T"]);
+        assert_eq!(
+            TypeDescription::parse("fn(&[T]) -> T", &[generic("T")]).unwrap(),
+            td
+        );
+    }
+
+    #[test]
+    fn compact_serde_test() {
+        let t: Type = serde_json::from_str(
+            r#"{
+                "inputs": {"in": {"type": "Option<T>"}},
+                "outputs": {"out": {"type": {"Type": {"name": "Vec", "type_parameters": ["T"]}}}},
+                "type_parameters": [{"name": "T", "where": []}],
+                "constructors": {}
+            }"#,
+        )
+        .expect("wrong format.");
+
+        let generic_t = Box::new(TypeDescription::Generic {
+            name: "T".to_string(),
+            type_parameters: None,
+        });
+        assert_eq!(
+            TypeDescription::Type {
+                name: "Option".to_string(),
+                type_parameters: Some(vec![generic_t.clone()])
+            },
+            t.inputs.as_ref().unwrap()["in"].input_type
+        );
+        assert_eq!(
+            TypeDescription::Type {
+                name: "Vec".to_string(),
+                type_parameters: Some(vec![generic_t])
+            },
+            t.outputs.as_ref().unwrap()["out"].output_type
+        );
+
+        let round_trip: Type = serde_json::from_str(&serde_json::to_string(&t).unwrap()).unwrap();
+        assert_eq!(t, round_trip);
+    }
+}
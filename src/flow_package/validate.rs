@@ -0,0 +1,468 @@
+use semver::VersionReq;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::package::{
+    Argument, ArgumentConstruction, Constructor, Module, Package, Type, TypeDescription,
+};
+use crate::package_manager::{split_qualified_type_name, PackageManager, TypeLookupError};
+use crate::templates::check_templates;
+
+/// Types usable without path in generated code that no package describes.
+const PRELUDE_TYPES: [&str; 6] = ["str", "String", "Vec", "Option", "Result", "Box"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The package cannot be used as it is, e.g. a constructor that does not exist is called.
+    Error,
+    /// The package is usable, but probably not as intended, e.g. an empty module.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found by [`Package::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// JSON pointer to the offending value in the package document.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.pointer, self.message)
+    }
+}
+
+/// A crate or module of a package.
+pub(crate) struct Scope<'a> {
+    /// JSON pointer to the crate or module.
+    pub pointer: String,
+    /// Path of the scope, e.g. `my_crate::module`.
+    pub path: String,
+    pub types: &'a HashMap<String, Type>,
+    pub modules: &'a HashMap<String, Module>,
+}
+
+/// All crates and modules of a package, sorted by path.
+pub(crate) fn scopes(package: &Package) -> Vec<Scope<'_>> {
+    let mut scopes = Vec::new();
+    let mut crates: Vec<_> = package.crates.iter().collect();
+    crates.sort_by_key(|(name, _)| *name);
+    for (name, krate) in crates {
+        collect_scopes(
+            Scope {
+                pointer: format!("/crates/{}", escape_pointer(name)),
+                path: name.clone(),
+                types: &krate.types,
+                modules: &krate.modules,
+            },
+            &mut scopes,
+        );
+    }
+    scopes
+}
+
+fn collect_scopes<'a>(scope: Scope<'a>, scopes: &mut Vec<Scope<'a>>) {
+    let mut modules: Vec<_> = scope.modules.iter().collect();
+    modules.sort_by_key(|(name, _)| *name);
+    let children: Vec<Scope> = modules
+        .into_iter()
+        .map(|(name, module)| Scope {
+            pointer: format!("{}/modules/{}", scope.pointer, escape_pointer(name)),
+            path: format!("{}::{}", scope.path, name),
+            types: &module.types,
+            modules: &module.modules,
+        })
+        .collect();
+    scopes.push(scope);
+    for child in children {
+        collect_scopes(child, scopes);
+    }
+}
+
+/// Sorted types of a scope with their JSON pointer and type name.
+pub(crate) fn scope_types<'a>(scope: &Scope<'a>) -> Vec<(String, String, &'a Type)> {
+    let mut types: Vec<_> = scope.types.iter().collect();
+    types.sort_by_key(|(name, _)| *name);
+    types
+        .into_iter()
+        .map(|(name, type_desc)| {
+            (
+                format!("{}/types/{}", scope.pointer, escape_pointer(name)),
+                format!("{}::{}", scope.path, name),
+                type_desc,
+            )
+        })
+        .collect()
+}
+
+/// Escapes a reference token of a JSON pointer, see RFC 6901.
+pub(crate) fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+impl Package {
+    /// Checks the package beyond its JSON structure: versions, referenced types, constructors,
+    /// context objects, templates and empty crates or modules.
+    ///
+    /// Types are looked up in the package itself and in `pack_man`, which does not need to
+    /// contain the package. Diagnostics are sorted by their location.
+    pub fn validate(&self, pack_man: &PackageManager) -> Vec<Diagnostic> {
+        let mut validator = Validator {
+            package: self,
+            pack_man,
+            diagnostics: Vec::new(),
+        };
+        validator.validate_package();
+        validator.diagnostics
+    }
+}
+
+struct Validator<'a> {
+    package: &'a Package,
+    pack_man: &'a PackageManager,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, severity: Severity, pointer: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            pointer: pointer.to_string(),
+            message,
+        });
+    }
+
+    fn validate_package(&mut self) {
+        if let Err(e) = self.package.semver() {
+            self.report(
+                Severity::Error,
+                "/version",
+                format!("'{}' is no semantic version: {}", self.package.version, e),
+            );
+        }
+
+        let mut dependencies: Vec<_> = self.package.dependencies.iter().collect();
+        dependencies.sort();
+        for (name, requirement) in dependencies {
+            if let Err(e) = VersionReq::parse(requirement) {
+                self.report(
+                    Severity::Error,
+                    &format!("/dependencies/{}", escape_pointer(name)),
+                    format!("'{}' is no version requirement: {}", requirement, e),
+                );
+            }
+        }
+
+        let mut context_objects: Vec<_> = self.package.context_objects.iter().collect();
+        context_objects.sort_by_key(|(name, _)| *name);
+        for (name, context_object) in context_objects {
+            self.check_type_description(
+                &format!("/context_objects/{}/type", escape_pointer(name)),
+                &context_object.object_type,
+                Severity::Warning,
+            );
+        }
+
+        for scope in scopes(self.package) {
+            if scope.types.is_empty() && scope.modules.is_empty() {
+                self.report(
+                    Severity::Warning,
+                    &scope.pointer,
+                    format!("'{}' contains neither types nor modules.", scope.path),
+                );
+            }
+            for (pointer, _, type_desc) in scope_types(&scope) {
+                self.validate_type(&pointer, type_desc);
+            }
+        }
+
+        for issue in check_templates(self.package) {
+            self.report(Severity::Error, &issue.pointer, issue.message);
+        }
+
+        self.diagnostics
+            .sort_by(|a, b| a.pointer.cmp(&b.pointer).then(a.severity.cmp(&b.severity)));
+    }
+
+    fn validate_type(&mut self, pointer: &str, type_desc: &Type) {
+        let mut declared = HashSet::new();
+        for (i, tp) in type_desc.type_parameters.iter().flatten().enumerate() {
+            if !declared.insert(tp.name.as_str()) {
+                self.report(
+                    Severity::Error,
+                    &format!("{}/type_parameters/{}/name", pointer, i),
+                    format!("Type parameter '{}' is declared twice.", tp.name),
+                );
+            }
+        }
+
+        // Port types are only compared with each other, so unknown ones may be fine.
+        for (kind, ports) in [
+            (
+                "inputs",
+                type_desc
+                    .inputs
+                    .iter()
+                    .flatten()
+                    .map(|(name, input)| (name, &input.input_type))
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                "outputs",
+                type_desc
+                    .outputs
+                    .iter()
+                    .flatten()
+                    .map(|(name, output)| (name, &output.output_type))
+                    .collect::<Vec<_>>(),
+            ),
+        ] {
+            let mut ports = ports;
+            ports.sort_by_key(|(name, _)| *name);
+            for (name, port_type) in ports {
+                self.check_type_description(
+                    &format!("{}/{}/{}/type", pointer, kind, escape_pointer(name)),
+                    port_type,
+                    Severity::Warning,
+                );
+            }
+        }
+
+        let mut constructors: Vec<_> = type_desc.constructors.iter().collect();
+        constructors.sort_by_key(|(name, _)| *name);
+        for (name, constructor) in constructors {
+            let (variant, arguments) = match constructor {
+                Constructor::NewWithArbitraryArgs { arguments, .. } => {
+                    ("NewWithArbitraryArgs", arguments)
+                }
+                Constructor::FromCode { arguments, .. } => ("FromCode", arguments),
+                _ => continue,
+            };
+            let pointer = format!(
+                "{}/constructors/{}/{}/arguments",
+                pointer,
+                escape_pointer(name),
+                variant
+            );
+
+            let mut names = HashSet::new();
+            for (i, arg) in arguments.iter().enumerate() {
+                let pointer = format!("{}/{}", pointer, i);
+                if !names.insert(arg.name.as_str()) {
+                    self.report(
+                        Severity::Error,
+                        &format!("{}/name", pointer),
+                        format!("Argument '{}' is declared twice.", arg.name),
+                    );
+                }
+                self.validate_argument(&pointer, arg);
+            }
+        }
+    }
+
+    fn validate_argument(&mut self, pointer: &str, arg: &Argument) {
+        match &arg.construction {
+            ArgumentConstruction::Constructor(constructor_name) => {
+                self.check_type_description(
+                    &format!("{}/type", pointer),
+                    &arg.arg_type,
+                    Severity::Error,
+                );
+                // Constructors of generic and structural types depend on the binding.
+                let TypeDescription::Type { name, .. } = arg.arg_type.as_ref() else {
+                    return;
+                };
+                if let Some(arg_type) = self.lookup(name).ok().flatten() {
+                    if !arg_type.constructors.contains_key(constructor_name) {
+                        self.report(
+                            Severity::Error,
+                            &format!("{}/construction/Constructor", pointer),
+                            format!("Type '{}' has no constructor '{}'.", name, constructor_name),
+                        );
+                    }
+                }
+            }
+            ArgumentConstruction::ExistingObject() => {
+                self.check_type_description(
+                    &format!("{}/type", pointer),
+                    &arg.arg_type,
+                    Severity::Warning,
+                );
+                let context_object = match self.package.context_objects.get(&arg.name) {
+                    Some(context_object) => context_object,
+                    None => match self.pack_man.context_object(&arg.name) {
+                        Ok(context_object) => context_object,
+                        Err(_) => {
+                            self.report(
+                                Severity::Error,
+                                &format!("{}/name", pointer),
+                                format!(
+                                    "'{}' is no context object of the package or a registered one.",
+                                    arg.name
+                                ),
+                            );
+                            return;
+                        }
+                    },
+                };
+                // Generic argument types depend on the binding and are checked on emission.
+                let no_binding = HashMap::new();
+                if let (Ok(expected), Ok(found)) = (
+                    context_object.object_type.emit_rust_type(&no_binding),
+                    arg.arg_type.emit_rust_type(&no_binding),
+                ) {
+                    if expected != found {
+                        self.report(
+                            Severity::Error,
+                            &format!("{}/type", pointer),
+                            format!(
+                                "Argument '{}' has type '{}', but context object '{}' is '{}'.",
+                                arg.name, found, arg.name, expected
+                            ),
+                        );
+                    }
+                }
+            }
+            ArgumentConstruction::Shared(_) => self.check_type_description(
+                &format!("{}/type", pointer),
+                &arg.arg_type,
+                Severity::Warning,
+            ),
+        }
+    }
+
+    /// Reports all type names in `td` that cannot be found. Names without path that are neither
+    /// built-in nor prelude types are most likely undeclared type parameters and always errors.
+    fn check_type_description(&mut self, pointer: &str, td: &TypeDescription, severity: Severity) {
+        if let TypeDescription::Type { name, .. } = td {
+            match self.lookup(name) {
+                Ok(Some(_)) => {}
+                Ok(None) if PRELUDE_TYPES.contains(&name.as_str()) => {}
+                Ok(None) if !split_qualified_type_name(name).1.contains("::") => self.report(
+                    Severity::Error,
+                    pointer,
+                    format!(
+                        "'{}' is neither a built-in type nor a declared type parameter.",
+                        name
+                    ),
+                ),
+                Ok(None) => self.report(
+                    severity,
+                    pointer,
+                    format!("Type '{}' is not described by any package.", name),
+                ),
+                Err(e) => self.report(Severity::Error, pointer, e.to_string()),
+            }
+        }
+        for child in td.children() {
+            self.check_type_description(pointer, child, severity);
+        }
+    }
+
+    /// Looks a type up in the validated package first, then in the package manager.
+    fn lookup(&self, type_name: &str) -> Result<Option<&Type>, TypeLookupError> {
+        let (package_name, path) = split_qualified_type_name(type_name);
+        if package_name.is_none_or(|p| p == self.package.name) {
+            let type_ids: Vec<&str> = path.split("::").collect();
+            if let Some(type_desc) = self.pack_man.get_type_from_package(&type_ids, self.package) {
+                return Ok(Some(type_desc));
+            }
+        }
+
+        match self.pack_man.resolve_type_matching(
+            type_name,
+            &HashMap::new(),
+            Some(&self.package.name),
+        ) {
+            Ok(resolved) => Ok(Some(resolved.type_desc)),
+            Err(TypeLookupError::NotFound(_) | TypeLookupError::UnknownPackage { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE_JSON: &str = r#"
+{
+    "name": "nodes",
+    "version": "1.0.0",
+    "dependencies": {"math": "not a requirement"},
+    "crates": {
+    "nodes": {
+        "types": {
+        "Scale": {
+            "inputs": {"input": {"type": "U"}},
+            "outputs": {"output": {"type": "external::Value"}},
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {"New": {"NewWithArbitraryArgs": {
+                "function_name": "new",
+                "arguments": [
+                    {"type": "nodes::Range<T>", "name": "range", "passing": "Move", "construction": {"Constructor": "Missing"}},
+                    {"type": "nodes::Missing", "name": "other", "passing": "Move", "construction": {"Constructor": "New"}},
+                    {"type": "T", "name": "range", "passing": "Move", "construction": {"Constructor": "Default"}},
+                    {"type": "metrics::Registry", "name": "metrics", "passing": "Reference", "construction": {"ExistingObject": []}},
+                    {"type": "()", "name": "change_observer", "passing": "Clone", "construction": {"ExistingObject": []}},
+                    {"type": "Vec<Option<T>>", "name": "labels", "passing": "Move", "construction": {"Constructor": "New"}},
+                    {"type": "String", "name": "unit", "passing": "Move", "construction": {"Shared": "unit"}}
+                ]
+            }}}
+        },
+        "Range": {
+            "inputs": null,
+            "outputs": null,
+            "type_parameters": [{"name": "T", "where": []}],
+            "constructors": {"New": {"New": {}}}
+        }
+        },
+        "modules": {"empty": {"types": {}, "modules": {}}}
+    }
+    }
+}
+        "#;
+
+    #[test]
+    fn validate_test() {
+        let pm = PackageManager::new();
+        let package: Package = serde_json::from_str(PACKAGE_JSON).expect("wrong format.");
+        let scale = "/crates/nodes/types/Scale";
+        let arguments = format!("{}/constructors/New/NewWithArbitraryArgs/arguments", scale);
+
+        let diagnostics: Vec<(Severity, String)> = package
+            .validate(&pm)
+            .into_iter()
+            .map(|d| (d.severity, d.pointer))
+            .collect();
+        assert_eq!(
+            vec![
+                (Severity::Warning, "/crates/nodes/modules/empty".to_string()),
+                (
+                    Severity::Error,
+                    format!("{}/0/construction/Constructor", arguments)
+                ),
+                (Severity::Error, format!("{}/1/type", arguments)),
+                (Severity::Error, format!("{}/2/name", arguments)),
+                (Severity::Error, format!("{}/3/name", arguments)),
+                (Severity::Warning, format!("{}/3/type", arguments)),
+                (Severity::Error, format!("{}/4/type", arguments)),
+                (Severity::Error, format!("{}/inputs/input/type", scale)),
+                (Severity::Warning, format!("{}/outputs/output/type", scale)),
+                (Severity::Error, "/dependencies/math".to_string()),
+            ],
+            diagnostics
+        );
+    }
+}
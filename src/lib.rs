@@ -1,4 +1,10 @@
 pub mod flow_package;
 
+use self::flow_package::extract;
+use self::flow_package::flow;
+use self::flow_package::inference;
 use self::flow_package::package;
 use self::flow_package::package_manager;
+use self::flow_package::templates;
+use self::flow_package::type_check;
+use self::flow_package::validate;
@@ -0,0 +1,94 @@
+//! Nodes of the rustdoc JSON fixture `../nodes.json`, regenerate it in this folder with
+//!
+//! ```text
+//! RUSTC_BOOTSTRAP=1 rustdoc -Z unstable-options --output-format json --crate-name nodes \
+//!     src/lib.rs -o ..
+//! ```
+//!
+//! The crate has no dependencies, so the flowrs and serde types are stand-ins.
+
+pub mod connection {
+    pub struct Input<T>(Option<T>);
+    pub struct Output<T>(Option<T>);
+
+    impl<T> Input<T> {
+        pub fn new() -> Self {
+            Self(None)
+        }
+    }
+
+    impl<T> Output<T> {
+        pub fn new() -> Self {
+            Self(None)
+        }
+    }
+}
+
+pub mod node {
+    pub struct ChangeObserver;
+    pub struct Context;
+}
+
+pub mod serde {
+    pub trait Deserialize<'de>: Sized {}
+}
+
+mod math;
+
+pub use math::{Add, Scale};
+
+pub mod sinks {
+    use crate::connection::Input;
+
+    #[derive(Clone, Default)]
+    pub struct Debug<T: Clone>
+    where
+        T: std::fmt::Debug,
+    {
+        pub input: Input<T>,
+    }
+
+    impl<T: Clone + std::fmt::Debug> Debug<T> {
+        pub fn new() -> Self {
+            Self {
+                input: Input::new(),
+            }
+        }
+    }
+}
+
+pub mod sources {
+    pub mod timer {
+        use crate::connection::Output;
+        use crate::node::ChangeObserver;
+
+        pub struct Timer {
+            pub tick: Output<()>,
+            interval: u64,
+        }
+
+        impl Timer {
+            pub fn new(_change_observer: Option<&ChangeObserver>) -> Self {
+                Self::with_interval(1000)
+            }
+
+            pub fn with_interval(interval: u64) -> Self {
+                Self {
+                    tick: Output::new(),
+                    interval,
+                }
+            }
+
+            pub fn interval(&self) -> u64 {
+                self.interval
+            }
+        }
+    }
+
+    pub use self::timer::Timer as Clock;
+}
+
+/// Re-exports the crate root into itself, a cycle the importer has to stop at.
+pub mod prelude {
+    pub use crate::*;
+}
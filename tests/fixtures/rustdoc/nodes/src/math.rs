@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use crate::connection::{Input, Output};
+use crate::node::{ChangeObserver, Context};
+use crate::serde::Deserialize;
+
+pub struct Add<I1, I2, O>
+where
+    I1: std::ops::Add<I2, Output = O> + Send,
+{
+    pub input_1: Input<I1>,
+    pub input_2: Input<I2>,
+    pub output: Output<Option<O>>,
+}
+
+impl<I1, I2, O> Add<I1, I2, O>
+where
+    I1: std::ops::Add<I2, Output = O> + Send,
+{
+    pub fn new(_change_observer: Option<&ChangeObserver>) -> Self {
+        Self {
+            input_1: Input::new(),
+            input_2: Input::new(),
+            output: Output::new(),
+        }
+    }
+
+    pub fn with_context(
+        change_observer: Option<&ChangeObserver>,
+        _context: &Context,
+    ) -> Result<Self, String> {
+        Ok(Self::new(change_observer))
+    }
+}
+
+pub struct Scale {
+    pub input: Input<f32>,
+    pub output: Output<Vec<f32>>,
+    pub factors: Output<HashMap<String, [f32; 2]>>,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self {
+            input: Input::new(),
+            output: Output::new(),
+            factors: Output::new(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Scale {}